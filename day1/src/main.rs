@@ -1,233 +1,1085 @@
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::BufReader;
+use task1::Policy;
 
 mod task1 {
-    fn first_digit(str: &str) -> u32 {
-        for c in str.chars() {
-            if c.is_ascii_digit() {
-                return c.to_digit(10).unwrap();
+    use std::io::BufRead;
+    use std::thread;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Kind {
+        Digit,
+        Word,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Token {
+        pub value: u32,
+        pub kind: Kind,
+        pub start: usize,
+        pub end: usize,
+    }
+
+    impl Token {
+        pub fn overlaps(&self, other: &Token) -> bool {
+            self != other && self.start < other.end && other.start < self.end
+        }
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let two = Token {
+            value: 2,
+            kind: Kind::Word,
+            start: 0,
+            end: 3,
+        };
+        let one = Token {
+            value: 1,
+            kind: Kind::Word,
+            start: 2,
+            end: 5,
+        };
+        let four = Token {
+            value: 4,
+            kind: Kind::Digit,
+            start: 5,
+            end: 6,
+        };
+        assert!(two.overlaps(&one));
+        assert!(one.overlaps(&two));
+        assert!(!one.overlaps(&four));
+        assert!(!two.overlaps(&two));
+    }
+
+    struct Node {
+        children: Vec<(u8, usize)>,
+        value: Option<(u32, Kind)>,
+    }
+
+    impl Node {
+        fn new() -> Self {
+            Self {
+                children: vec![],
+                value: None,
+            }
+        }
+
+        fn child(&self, byte: u8) -> Option<usize> {
+            self.children
+                .iter()
+                .find(|(b, _)| *b == byte)
+                .map(|(_, i)| *i)
+        }
+    }
+
+    struct Trie {
+        nodes: Vec<Node>,
+    }
+
+    impl Trie {
+        fn new() -> Self {
+            Self {
+                nodes: vec![Node::new()],
+            }
+        }
+
+        fn insert<I>(&mut self, bytes: I, value: u32, kind: Kind)
+        where
+            I: Iterator<Item = u8>,
+        {
+            let mut current = 0;
+            for b in bytes {
+                current = match self.nodes[current].child(b) {
+                    Some(next) => next,
+                    None => {
+                        self.nodes.push(Node::new());
+                        let next = self.nodes.len() - 1;
+                        self.nodes[current].children.push((b, next));
+                        next
+                    }
+                };
+            }
+            self.nodes[current].value = Some((value, kind));
+        }
+
+        fn match_at<I>(&self, bytes: I) -> Option<(u32, Kind, usize)>
+        where
+            I: Iterator<Item = u8>,
+        {
+            let mut current = 0;
+            let mut longest = None;
+            for (i, b) in bytes.enumerate() {
+                current = match self.nodes[current].child(b) {
+                    Some(next) => next,
+                    None => break,
+                };
+                if let Some((value, kind)) = self.nodes[current].value {
+                    longest = Some((value, kind, i + 1));
+                }
+            }
+            longest
+        }
+    }
+
+    #[test]
+    fn test_trie() {
+        let mut trie = Trie::new();
+        trie.insert("one".bytes(), 1, Kind::Word);
+        trie.insert("two".bytes(), 2, Kind::Word);
+        trie.insert("2".bytes(), 2, Kind::Digit);
+        assert_eq!(trie.match_at("one".bytes()), Some((1, Kind::Word, 3)));
+        assert_eq!(trie.match_at("twone".bytes()), Some((2, Kind::Word, 3)));
+        assert_eq!(trie.match_at("2x".bytes()), Some((2, Kind::Digit, 1)));
+        assert_eq!(trie.match_at("on".bytes()), None);
+        assert_eq!(trie.match_at("xone".bytes()), None);
+
+        trie.insert("ein".bytes(), 1, Kind::Word);
+        trie.insert("einsam".bytes(), 0, Kind::Word);
+        assert_eq!(trie.match_at("eins".bytes()), Some((1, Kind::Word, 3)));
+        assert_eq!(
+            trie.match_at("einsamkeit".bytes()),
+            Some((0, Kind::Word, 6))
+        );
+    }
+
+    pub struct DigitLexicon {
+        forward: Trie,
+        backward: Trie,
+    }
+
+    impl DigitLexicon {
+        pub fn new(words: &[(&str, u32)]) -> Self {
+            let mut lexicon = Self {
+                forward: Trie::new(),
+                backward: Trie::new(),
+            };
+            for digit in 0..10 {
+                lexicon.insert(&digit.to_string(), digit, Kind::Digit);
+            }
+            for (word, value) in words {
+                lexicon.insert(word, *value, Kind::Word);
+            }
+            lexicon
+        }
+
+        pub fn with_full_width_digits(mut self) -> Self {
+            for digit in 0..10 {
+                let c = char::from_u32('０' as u32 + digit).unwrap();
+                self.insert(&c.to_string(), digit, Kind::Digit);
+            }
+            self
+        }
+
+        fn insert(&mut self, word: &str, value: u32, kind: Kind) {
+            self.forward.insert(word.bytes(), value, kind);
+            self.backward.insert(word.bytes().rev(), value, kind);
+        }
+
+        pub fn empty() -> Self {
+            Self::new(&[])
+        }
+
+        pub fn first_token(&self, str: &str) -> Option<Token> {
+            let bytes = str.as_bytes();
+            (0..bytes.len()).find_map(|i| {
+                let (value, kind, length) = self.forward.match_at(bytes[i..].iter().copied())?;
+                Some(Token {
+                    value,
+                    kind,
+                    start: i,
+                    end: i + length,
+                })
+            })
+        }
+
+        pub fn last_token(&self, str: &str) -> Option<Token> {
+            let bytes = str.as_bytes();
+            (0..bytes.len()).rev().find_map(|i| {
+                let (value, kind, length) =
+                    self.backward.match_at(bytes[..=i].iter().rev().copied())?;
+                Some(Token {
+                    value,
+                    kind,
+                    start: i + 1 - length,
+                    end: i + 1,
+                })
+            })
+        }
+
+        pub fn tokens(&self, str: &str) -> Vec<Token> {
+            let bytes = str.as_bytes();
+            (0..bytes.len())
+                .filter_map(|i| {
+                    let (value, kind, length) =
+                        self.forward.match_at(bytes[i..].iter().copied())?;
+                    Some(Token {
+                        value,
+                        kind,
+                        start: i,
+                        end: i + length,
+                    })
+                })
+                .collect()
+        }
+
+        pub fn first_digit(&self, str: &str) -> Option<u32> {
+            self.first_token(str).map(|t| t.value)
+        }
+
+        pub fn last_digit(&self, str: &str) -> Option<u32> {
+            self.last_token(str).map(|t| t.value)
+        }
+
+        pub fn process_line(&self, str: &str) -> Option<u32> {
+            Some(self.first_digit(str)? * 10 + self.last_digit(str)?)
+        }
+    }
+
+    pub fn lexicon() -> DigitLexicon {
+        DigitLexicon::empty()
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Policy {
+        Skip,
+        Zero,
+        Error,
+    }
+
+    impl Policy {
+        pub fn from_str(input: &str) -> Option<Self> {
+            match input {
+                "skip" => Some(Policy::Skip),
+                "zero" => Some(Policy::Zero),
+                "error" => Some(Policy::Error),
+                _ => None,
             }
         }
-        0
     }
 
-    fn last_digit(str: &str) -> u32 {
-        for c in str.chars().rev() {
-            if c.is_ascii_digit() {
-                return c.to_digit(10).unwrap();
+    #[derive(Debug, PartialEq)]
+    pub struct MissingDigits {
+        pub line_number: usize,
+    }
+
+    impl std::fmt::Display for MissingDigits {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "line {} contains no digits", self.line_number)
+        }
+    }
+
+    impl std::error::Error for MissingDigits {}
+
+    #[derive(Debug)]
+    pub enum CalibrationError {
+        Io(std::io::Error),
+        MissingDigits(MissingDigits),
+    }
+
+    impl From<std::io::Error> for CalibrationError {
+        fn from(e: std::io::Error) -> Self {
+            CalibrationError::Io(e)
+        }
+    }
+
+    impl From<MissingDigits> for CalibrationError {
+        fn from(e: MissingDigits) -> Self {
+            CalibrationError::MissingDigits(e)
+        }
+    }
+
+    impl From<CalibrationError> for std::io::Error {
+        fn from(e: CalibrationError) -> Self {
+            match e {
+                CalibrationError::Io(e) => e,
+                CalibrationError::MissingDigits(e) => {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+                }
             }
         }
-        0
     }
 
-    fn process_line(str: &str) -> u32 {
-        first_digit(str) * 10 + last_digit(str)
+    #[derive(Debug, PartialEq, Default)]
+    pub struct Calibration {
+        pub sum: u64,
+        pub lines: usize,
+        pub affected: usize,
+    }
+
+    impl Calibration {
+        fn merge(&mut self, other: Calibration) {
+            self.sum += other.sum;
+            self.lines += other.lines;
+            self.affected += other.affected;
+        }
+    }
+
+    pub fn calibrate(
+        str: &str,
+        lexicon: &DigitLexicon,
+        policy: Policy,
+    ) -> Result<Calibration, MissingDigits> {
+        let mut calibration = Calibration::default();
+        for (i, l) in str.lines().enumerate() {
+            match lexicon.process_line(l) {
+                Some(value) => {
+                    calibration.sum += value as u64;
+                    calibration.lines += 1;
+                }
+                None => match policy {
+                    Policy::Skip => calibration.affected += 1,
+                    Policy::Zero => {
+                        calibration.affected += 1;
+                        calibration.lines += 1;
+                    }
+                    Policy::Error => return Err(MissingDigits { line_number: i + 1 }),
+                },
+            }
+        }
+        Ok(calibration)
     }
 
-    pub fn handle_input(str: &str) -> u32 {
-        let mut ret = 0;
-        for l in str.lines() {
-            ret += process_line(l);
+    fn read_chunk<R: BufRead>(reader: &mut R, lines: usize) -> std::io::Result<(String, usize)> {
+        let mut chunk = String::new();
+        let mut count = 0;
+        while count < lines && reader.read_line(&mut chunk)? != 0 {
+            count += 1;
         }
-        ret
+        Ok((chunk, count))
+    }
+
+    pub fn calibrate_reader<R: BufRead>(
+        mut reader: R,
+        lexicon: &DigitLexicon,
+        policy: Policy,
+        threads: usize,
+        chunk_lines: usize,
+    ) -> Result<Calibration, CalibrationError> {
+        let mut total = Calibration::default();
+        let mut line_offset = 0;
+        loop {
+            let mut chunks = vec![];
+            for _ in 0..threads.max(1) {
+                let (chunk, count) = read_chunk(&mut reader, chunk_lines.max(1))?;
+                if count == 0 {
+                    break;
+                }
+                chunks.push((chunk, count));
+            }
+            if chunks.is_empty() {
+                return Ok(total);
+            }
+
+            let results: Vec<Result<Calibration, MissingDigits>> = if chunks.len() == 1 {
+                vec![calibrate(&chunks[0].0, lexicon, policy)]
+            } else {
+                thread::scope(|scope| {
+                    let handles: Vec<_> = chunks
+                        .iter()
+                        .map(|(chunk, _)| scope.spawn(move || calibrate(chunk, lexicon, policy)))
+                        .collect();
+                    handles.into_iter().map(|h| h.join().unwrap()).collect()
+                })
+            };
+
+            for ((_, count), result) in chunks.iter().zip(results) {
+                match result {
+                    Ok(calibration) => total.merge(calibration),
+                    Err(e) => {
+                        return Err(MissingDigits {
+                            line_number: line_offset + e.line_number,
+                        }
+                        .into())
+                    }
+                }
+                line_offset += count;
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_chunk() {
+        let mut reader = "a1\nb2\r\nc3".as_bytes();
+        assert_eq!(
+            read_chunk(&mut reader, 2).unwrap(),
+            ("a1\nb2\r\n".to_string(), 2)
+        );
+        assert_eq!(read_chunk(&mut reader, 2).unwrap(), ("c3".to_string(), 1));
+        assert_eq!(read_chunk(&mut reader, 2).unwrap(), ("".to_string(), 0));
+    }
+
+    #[test]
+    fn test_calibrate_reader() {
+        let input = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n".repeat(25);
+        for (threads, chunk_lines) in [(1, 1), (1, 1000), (4, 3), (8, 7)] {
+            assert_eq!(
+                calibrate_reader(
+                    input.as_bytes(),
+                    &lexicon(),
+                    Policy::Error,
+                    threads,
+                    chunk_lines
+                )
+                .unwrap(),
+                Calibration {
+                    sum: 142 * 25,
+                    lines: 100,
+                    affected: 0
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_calibrate_reader_policy() {
+        let input = "1abc2\n".repeat(10) + "none\n" + &"7\n".repeat(10) + "nada";
+        assert_eq!(
+            calibrate_reader(input.as_bytes(), &lexicon(), Policy::Skip, 3, 4).unwrap(),
+            Calibration {
+                sum: 12 * 10 + 77 * 10,
+                lines: 20,
+                affected: 2
+            }
+        );
+        match calibrate_reader(input.as_bytes(), &lexicon(), Policy::Error, 3, 4) {
+            Err(CalibrationError::MissingDigits(e)) => assert_eq!(e.line_number, 11),
+            _ => panic!(),
+        }
+        match calibrate_reader(&[b'1', 0xff, b'\n'][..], &lexicon(), Policy::Error, 1, 4) {
+            Err(CalibrationError::Io(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::InvalidData)
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_calibrate_policy() {
+        let input = "1abc2
+        no digits here
+        treb7uchet
+        one";
+        assert_eq!(
+            calibrate(input, &lexicon(), Policy::Skip),
+            Ok(Calibration {
+                sum: 89,
+                lines: 2,
+                affected: 2
+            })
+        );
+        assert_eq!(
+            calibrate(input, &lexicon(), Policy::Zero),
+            Ok(Calibration {
+                sum: 89,
+                lines: 4,
+                affected: 2
+            })
+        );
+        assert_eq!(
+            calibrate(input, &lexicon(), Policy::Error),
+            Err(MissingDigits { line_number: 2 })
+        );
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(Policy::from_str("skip"), Some(Policy::Skip));
+        assert_eq!(Policy::from_str("zero"), Some(Policy::Zero));
+        assert_eq!(Policy::from_str("error"), Some(Policy::Error));
+        assert_eq!(Policy::from_str("lenient"), None);
     }
 
     #[test]
     fn test_first_digit() {
-        assert_eq!(first_digit("1abc2"), 1);
-        assert_eq!(first_digit("pqr3stu8vwx"), 3);
-        assert_eq!(first_digit("a1b2c3d4e5f"), 1);
-        assert_eq!(first_digit("treb7uchet"), 7);
+        let lexicon = DigitLexicon::empty();
+        assert_eq!(lexicon.first_digit("1abc2"), Some(1));
+        assert_eq!(lexicon.first_digit("pqr3stu8vwx"), Some(3));
+        assert_eq!(lexicon.first_digit("a1b2c3d4e5f"), Some(1));
+        assert_eq!(lexicon.first_digit("treb7uchet"), Some(7));
+        assert_eq!(lexicon.first_digit("one2"), Some(2));
+        assert_eq!(lexicon.first_digit("abc"), None);
     }
 
     #[test]
     fn test_last_digit() {
-        assert_eq!(last_digit("1abc2"), 2);
-        assert_eq!(last_digit("pqr3stu8vwx"), 8);
-        assert_eq!(last_digit("a1b2c3d4e5f"), 5);
-        assert_eq!(last_digit("treb7uchet"), 7);
+        let lexicon = DigitLexicon::empty();
+        assert_eq!(lexicon.last_digit("1abc2"), Some(2));
+        assert_eq!(lexicon.last_digit("pqr3stu8vwx"), Some(8));
+        assert_eq!(lexicon.last_digit("a1b2c3d4e5f"), Some(5));
+        assert_eq!(lexicon.last_digit("treb7uchet"), Some(7));
+        assert_eq!(lexicon.last_digit("2one"), Some(2));
+        assert_eq!(lexicon.last_digit("abc"), None);
+    }
+
+    #[test]
+    fn test_tokens() {
+        let lexicon = DigitLexicon::new(&[("one", 1), ("two", 2)]);
+        assert_eq!(
+            lexicon.first_token("xtwone3"),
+            Some(Token {
+                value: 2,
+                kind: Kind::Word,
+                start: 1,
+                end: 4
+            })
+        );
+        assert_eq!(
+            lexicon.last_token("xtwone3"),
+            Some(Token {
+                value: 3,
+                kind: Kind::Digit,
+                start: 6,
+                end: 7
+            })
+        );
+        assert_eq!(
+            lexicon.last_token("é😀twonex"),
+            Some(Token {
+                value: 1,
+                kind: Kind::Word,
+                start: 8,
+                end: 11
+            })
+        );
+    }
+
+    #[test]
+    fn test_all_tokens() {
+        let lexicon = DigitLexicon::new(&[("one", 1), ("two", 2)]);
+        let tokens: Vec<(u32, usize, usize)> = lexicon
+            .tokens("twone3")
+            .iter()
+            .map(|t| (t.value, t.start, t.end))
+            .collect();
+        assert_eq!(tokens, vec![(2, 0, 3), (1, 2, 5), (3, 5, 6)]);
+    }
+
+    #[test]
+    fn test_unicode() {
+        let lexicon = DigitLexicon::empty();
+        assert_eq!(lexicon.process_line("🎉a4é🚀"), Some(44));
+        assert_eq!(lexicon.process_line("ñ1😀😀2ü"), Some(12));
+        assert_eq!(lexicon.process_line("日本3語7"), Some(37));
+        assert_eq!(lexicon.process_line("１２３"), None);
+        assert_eq!(lexicon.process_line("🎉"), None);
+    }
+
+    #[test]
+    fn test_full_width_digits() {
+        let lexicon = DigitLexicon::empty().with_full_width_digits();
+        assert_eq!(lexicon.process_line("１２３"), Some(13));
+        assert_eq!(lexicon.process_line("é８🎉4ü"), Some(84));
+        assert_eq!(lexicon.process_line("5日本０"), Some(50));
     }
 
     #[test]
     fn test_process_line() {
-        assert_eq!(process_line("1abc2"), 12);
-        assert_eq!(process_line("pqr3stu8vwx"), 38);
-        assert_eq!(process_line("a1b2c3d4e5f"), 15);
-        assert_eq!(process_line("treb7uchet"), 77);
+        let lexicon = DigitLexicon::empty();
+        assert_eq!(lexicon.process_line("1abc2"), Some(12));
+        assert_eq!(lexicon.process_line("pqr3stu8vwx"), Some(38));
+        assert_eq!(lexicon.process_line("a1b2c3d4e5f"), Some(15));
+        assert_eq!(lexicon.process_line("treb7uchet"), Some(77));
     }
 
     #[test]
     fn test_sample_input() {
         assert_eq!(
-            handle_input(
+            calibrate(
                 "1abc2
         pqr3stu8vwx
         a1b2c3d4e5f
-        treb7uchet"
-            ),
+        treb7uchet",
+                &lexicon(),
+                Policy::Error
+            )
+            .unwrap()
+            .sum,
             142
         );
     }
 }
 
 mod task2 {
-    use std::collections::HashMap;
-
-    fn string_to_digit(str: &str) -> u32 {
-        let translation_table = HashMap::from([
-            ("one", 1),
-            ("two", 2),
-            ("three", 3),
-            ("four", 4),
-            ("five", 5),
+    use crate::task1::DigitLexicon;
+    #[cfg(test)]
+    use crate::task1::{calibrate, Calibration, MissingDigits, Policy};
+
+    const ENGLISH: [(&str, u32); 9] = [
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+    ];
+
+    pub fn lexicon() -> DigitLexicon {
+        DigitLexicon::new(&ENGLISH)
+    }
+
+    #[test]
+    fn test_sample_input() {
+        assert_eq!(
+            calibrate(
+                "two1nine
+            eightwothree
+            abcone2threexyz
+            xtwone3four
+            4nineeightseven2
+            zoneight234
+            7pqrstsixteen",
+                &lexicon(),
+                Policy::Error
+            )
+            .unwrap()
+            .sum,
+            281
+        );
+    }
+
+    #[test]
+    fn test_calibrate_policy() {
+        let input = "two1nine
+        xyz
+        eightwo";
+        assert_eq!(
+            calibrate(input, &lexicon(), Policy::Zero),
+            Ok(Calibration {
+                sum: 111,
+                lines: 3,
+                affected: 1
+            })
+        );
+        assert_eq!(
+            calibrate(input, &lexicon(), Policy::Error),
+            Err(MissingDigits { line_number: 2 })
+        );
+    }
+
+    #[test]
+    fn test_process_line() {
+        let lexicon = DigitLexicon::new(&ENGLISH);
+        assert_eq!(lexicon.process_line("two1nine"), Some(29));
+        assert_eq!(lexicon.process_line("eightwothree"), Some(83));
+        assert_eq!(lexicon.process_line("abcone2threexyz"), Some(13));
+        assert_eq!(lexicon.process_line("xtwone3four"), Some(24));
+        assert_eq!(lexicon.process_line("4nineeightseven2"), Some(42));
+        assert_eq!(lexicon.process_line("zoneight234"), Some(14));
+        assert_eq!(lexicon.process_line("7pqrstsixteen"), Some(76));
+    }
+
+    #[test]
+    fn test_last_digit() {
+        let lexicon = DigitLexicon::new(&ENGLISH);
+        assert_eq!(lexicon.last_digit("two1nine"), Some(9));
+        assert_eq!(lexicon.last_digit("eightwothree"), Some(3));
+        assert_eq!(lexicon.last_digit("abcone2threexyz"), Some(3));
+        assert_eq!(lexicon.last_digit("xtwone3four"), Some(4));
+        assert_eq!(lexicon.last_digit("4nineeightseven2"), Some(2));
+        assert_eq!(lexicon.last_digit("zoneight234"), Some(4));
+        assert_eq!(lexicon.last_digit("7pqrstsixteen"), Some(6));
+        assert_eq!(lexicon.last_digit("twone"), Some(1));
+        assert_eq!(lexicon.last_digit("sevenine"), Some(9));
+    }
+
+    #[test]
+    fn test_first_digit() {
+        let lexicon = DigitLexicon::new(&ENGLISH);
+        assert_eq!(lexicon.first_digit("two1nine"), Some(2));
+        assert_eq!(lexicon.first_digit("eightwothree"), Some(8));
+        assert_eq!(lexicon.first_digit("abcone2threexyz"), Some(1));
+        assert_eq!(lexicon.first_digit("xtwone3four"), Some(2));
+        assert_eq!(lexicon.first_digit("4nineeightseven2"), Some(4));
+        assert_eq!(lexicon.first_digit("zoneight234"), Some(1));
+        assert_eq!(lexicon.first_digit("7pqrstsixteen"), Some(7));
+        assert_eq!(lexicon.first_digit("twone"), Some(2));
+        assert_eq!(lexicon.first_digit("sevenine"), Some(7));
+    }
+
+    #[test]
+    fn test_words() {
+        let lexicon = DigitLexicon::new(&ENGLISH);
+        assert_eq!(lexicon.first_digit("one"), Some(1));
+        assert_eq!(lexicon.last_digit("one"), Some(1));
+        assert_eq!(lexicon.first_digit("two"), Some(2));
+        assert_eq!(lexicon.last_digit("two"), Some(2));
+        assert_eq!(lexicon.first_digit("three"), Some(3));
+        assert_eq!(lexicon.last_digit("three"), Some(3));
+        assert_eq!(lexicon.first_digit("four"), Some(4));
+        assert_eq!(lexicon.last_digit("four"), Some(4));
+        assert_eq!(lexicon.first_digit("five"), Some(5));
+        assert_eq!(lexicon.last_digit("five"), Some(5));
+        assert_eq!(lexicon.first_digit("six"), Some(6));
+        assert_eq!(lexicon.last_digit("six"), Some(6));
+        assert_eq!(lexicon.first_digit("seven"), Some(7));
+        assert_eq!(lexicon.last_digit("seven"), Some(7));
+        assert_eq!(lexicon.first_digit("eight"), Some(8));
+        assert_eq!(lexicon.last_digit("eight"), Some(8));
+        assert_eq!(lexicon.first_digit("nine"), Some(9));
+        assert_eq!(lexicon.last_digit("nine"), Some(9));
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let german = DigitLexicon::new(&[
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("vier", 4),
+            ("fünf", 5),
+            ("sechs", 6),
+            ("sieben", 7),
+            ("acht", 8),
+            ("neun", 9),
+        ]);
+        assert_eq!(german.process_line("xzweinsiebenfünfy"), Some(25));
+        assert_eq!(german.process_line("acht3two"), Some(83));
+
+        let french = DigitLexicon::new(&[
+            ("un", 1),
+            ("deux", 2),
+            ("trois", 3),
+            ("quatre", 4),
+            ("cinq", 5),
             ("six", 6),
-            ("seven", 7),
-            ("eight", 8),
-            ("nine", 9),
+            ("sept", 7),
+            ("huit", 8),
+            ("neuf", 9),
         ]);
-        translation_table[str]
+        assert_eq!(french.process_line("troisquatreun"), Some(31));
+        assert_eq!(french.process_line("one2deux"), Some(22));
     }
 
-    fn first_digit(str: &str) -> u32 {
-        let mut digit: Option<(usize, u32)> = None;
-        for (i, c) in str.chars().enumerate() {
-            if c.is_ascii_digit() {
-                digit = Some((i, c.to_digit(10).unwrap()));
-                break;
+    #[test]
+    fn test_unicode_words() {
+        let lexicon = DigitLexicon::new(&ENGLISH);
+        assert_eq!(lexicon.process_line("😀twoé🎉"), Some(22));
+        assert_eq!(lexicon.process_line("café🍰eightwo"), Some(82));
+        assert_eq!(lexicon.process_line("ééone😀1nineñ"), Some(19));
+        assert_eq!(lexicon.process_line("ønë3síx"), Some(33));
+
+        let lexicon = DigitLexicon::new(&[("fünf", 5), ("zwölf", 1)]).with_full_width_digits();
+        assert_eq!(lexicon.process_line("🎉fünf２"), Some(52));
+        assert_eq!(lexicon.process_line("３zwölf"), Some(31));
+    }
+
+    #[test]
+    fn test_extra_words() {
+        let lexicon = DigitLexicon::new(&[ENGLISH.as_slice(), &[("zero", 0)]].concat());
+        assert_eq!(lexicon.process_line("zero4nine"), Some(9));
+        assert_eq!(lexicon.process_line("onezero"), Some(10));
+        assert_eq!(
+            DigitLexicon::new(&ENGLISH).process_line("onezero"),
+            Some(11)
+        );
+    }
+}
+
+mod explain {
+    use crate::task1::{DigitLexicon, Kind, Token};
+
+    pub struct Explanation<'a> {
+        line_number: usize,
+        line: &'a str,
+        first: Option<Token>,
+        last: Option<Token>,
+        overlaps: Vec<(Token, Token)>,
+    }
+
+    impl<'a> Explanation<'a> {
+        fn new(line_number: usize, line: &'a str, lexicon: &DigitLexicon) -> Self {
+            let tokens = lexicon.tokens(line);
+            let mut overlaps = vec![];
+            for (i, a) in tokens.iter().enumerate() {
+                for b in &tokens[i + 1..] {
+                    if a.overlaps(b) {
+                        overlaps.push((*a, *b));
+                    }
+                }
+            }
+            Self {
+                line_number,
+                line,
+                first: lexicon.first_token(line),
+                last: lexicon.last_token(line),
+                overlaps,
             }
         }
 
-        let re =
-            regex::Regex::new("(one)|(two)|(three)|(four)|(five)|(six)|(seven)|(eight)|(nine)")
-                .unwrap();
-        match re.find(str) {
-            Some(m) => match digit {
-                Some((di, dval)) if di < m.start() => dval,
-                _ => string_to_digit(m.as_str()),
-            },
-            None => match digit {
-                Some((_, value)) => value,
-                None => panic!(),
-            },
+        fn value(&self) -> Option<u32> {
+            Some(self.first?.value * 10 + self.last?.value)
         }
-    }
 
-    fn last_digit(str: &str) -> u32 {
-        let mut digit: Option<(usize, u32)> = None;
-        for (i, c) in str.chars().rev().enumerate() {
-            if c.is_ascii_digit() {
-                digit = Some((str.len() - i - 1, c.to_digit(10).unwrap()));
-                break;
+        fn annotated_line(&self) -> String {
+            let mut marks = vec![];
+            match (self.first, self.last) {
+                (Some(first), Some(last)) if first == last => {
+                    marks.push((first.start, '['));
+                    marks.push((first.end, ']'));
+                }
+                (Some(first), Some(last)) => {
+                    marks.push((first.start, '['));
+                    marks.push((first.end, ']'));
+                    marks.push((last.start, '{'));
+                    marks.push((last.end, '}'));
+                }
+                _ => {}
+            }
+            marks.sort_by_key(|(position, mark)| (*position, matches!(mark, '[' | '{')));
+
+            let mut result = String::new();
+            let mut prev = 0;
+            for (position, mark) in marks {
+                result.push_str(&self.line[prev..position]);
+                result.push(mark);
+                prev = position;
             }
+            result.push_str(&self.line[prev..]);
+            result
         }
 
-        for dstr in [
-            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
-        ] {
-            let re = regex::Regex::new(dstr).unwrap();
-            digit = match re.find_iter(str).last() {
-                Some(m) => match digit {
-                    Some((di, _)) if di > m.start() => digit,
-                    _ => Some((m.start(), string_to_digit(m.as_str()))),
-                },
-                None => digit,
+        fn token_text(&self, token: &Token) -> String {
+            format!(
+                "{} {:?} = {} at {}..{}",
+                kind_name(token.kind),
+                &self.line[token.start..token.end],
+                token.value,
+                token.start,
+                token.end
+            )
+        }
+
+        fn optional_token_text(&self, token: &Option<Token>) -> String {
+            match token {
+                Some(t) => self.token_text(t),
+                None => "none".to_string(),
+            }
+        }
+
+        pub fn to_text(&self) -> String {
+            let value = match self.value() {
+                Some(v) => v.to_string(),
+                None => "no digits".to_string(),
+            };
+            let overlap = if self.overlaps.is_empty() {
+                ""
+            } else {
+                " [overlap]"
+            };
+            let mut result = format!(
+                "{}: {} => {value}{overlap}\n  first: {}\n  last:  {}\n",
+                self.line_number,
+                self.annotated_line(),
+                self.optional_token_text(&self.first),
+                self.optional_token_text(&self.last)
+            );
+            for (a, b) in &self.overlaps {
+                result.push_str(&format!(
+                    "  overlap: {} with {}\n",
+                    self.token_text(a),
+                    self.token_text(b)
+                ));
             }
+            result
         }
 
-        match digit {
-            Some((_, val)) => val,
-            None => panic!(),
+        fn token_json(&self, token: &Token) -> String {
+            format!(
+                "{{\"kind\":\"{}\",\"text\":{},\"value\":{},\"start\":{},\"end\":{}}}",
+                kind_name(token.kind),
+                json_string(&self.line[token.start..token.end]),
+                token.value,
+                token.start,
+                token.end
+            )
+        }
+
+        fn optional_token_json(&self, token: &Option<Token>) -> String {
+            match token {
+                Some(t) => self.token_json(t),
+                None => "null".to_string(),
+            }
+        }
+
+        pub fn to_json(&self) -> String {
+            let value = match self.value() {
+                Some(v) => v.to_string(),
+                None => "null".to_string(),
+            };
+            let overlaps: Vec<String> = self
+                .overlaps
+                .iter()
+                .map(|(a, b)| format!("[{},{}]", self.token_json(a), self.token_json(b)))
+                .collect();
+            format!(
+                "{{\"line\":{},\"text\":{},\"value\":{value},\"first\":{},\"last\":{},\"overlaps\":[{}]}}",
+                self.line_number,
+                json_string(self.line),
+                self.optional_token_json(&self.first),
+                self.optional_token_json(&self.last),
+                overlaps.join(",")
+            )
         }
     }
 
-    fn process_line(str: &str) -> u32 {
-        first_digit(str) * 10 + last_digit(str)
+    fn kind_name(kind: Kind) -> &'static str {
+        match kind {
+            Kind::Digit => "digit",
+            Kind::Word => "word",
+        }
     }
 
-    pub fn handle_input(str: &str) -> u32 {
-        let mut ret = 0;
-        for l in str.lines() {
-            ret += process_line(l);
+    fn json_string(str: &str) -> String {
+        let mut result = String::from("\"");
+        for c in str.chars() {
+            match c {
+                '"' => result.push_str("\\\""),
+                '\\' => result.push_str("\\\\"),
+                '\n' => result.push_str("\\n"),
+                '\r' => result.push_str("\\r"),
+                '\t' => result.push_str("\\t"),
+                c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+                c => result.push(c),
+            }
         }
-        ret
+        result.push('"');
+        result
     }
 
     #[test]
-    fn test_sample_input() {
-        assert_eq!(
-            handle_input(
-                "two1nine
-            eightwothree
-            abcone2threexyz
-            xtwone3four
-            4nineeightseven2
-            zoneight234
-            7pqrstsixteen"
-            ),
-            281
-        );
+    fn test_json_string() {
+        assert_eq!(json_string("two1"), "\"two1\"");
+        assert_eq!(json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(json_string("é\t\u{1}"), "\"é\\t\\u0001\"");
+    }
+
+    pub fn explain<'a>(input: &'a str, lexicon: &DigitLexicon) -> Vec<Explanation<'a>> {
+        input
+            .lines()
+            .enumerate()
+            .map(|(i, l)| Explanation::new(i + 1, l, lexicon))
+            .collect()
+    }
+
+    pub fn to_text(explanations: &[Explanation]) -> String {
+        explanations.iter().map(|e| e.to_text()).collect()
+    }
+
+    pub fn to_json(explanations: &[Explanation]) -> String {
+        let lines: Vec<String> = explanations.iter().map(|e| e.to_json()).collect();
+        format!("[{}]", lines.join(","))
     }
 
     #[test]
-    fn test_process_line() {
-        assert_eq!(process_line("two1nine"), 29);
-        assert_eq!(process_line("eightwothree"), 83);
-        assert_eq!(process_line("abcone2threexyz"), 13);
-        assert_eq!(process_line("xtwone3four"), 24);
-        assert_eq!(process_line("4nineeightseven2"), 42);
-        assert_eq!(process_line("zoneight234"), 14);
-        assert_eq!(process_line("7pqrstsixteen"), 76);
+    fn test_annotated_line() {
+        let lexicon = DigitLexicon::new(&[("one", 1), ("two", 2), ("eight", 8)]);
+        let annotated = |line| Explanation::new(1, line, &lexicon).annotated_line();
+        assert_eq!(annotated("two1nine"), "[two]{1}nine");
+        assert_eq!(annotated("xtwone"), "x[tw{o]ne}");
+        assert_eq!(annotated("a4b"), "a[4]b");
+        assert_eq!(annotated("é😀eightwo"), "é😀[eigh{t]wo}");
+        assert_eq!(annotated("abc"), "abc");
     }
 
     #[test]
-    fn test_last_digit() {
-        assert_eq!(last_digit("two1nine"), 9);
-        assert_eq!(last_digit("eightwothree"), 3);
-        assert_eq!(last_digit("abcone2threexyz"), 3);
-        assert_eq!(last_digit("xtwone3four"), 4);
-        assert_eq!(last_digit("4nineeightseven2"), 2);
-        assert_eq!(last_digit("zoneight234"), 4);
-        assert_eq!(last_digit("7pqrstsixteen"), 6);
-        assert_eq!(last_digit("twone"), 1);
-        assert_eq!(last_digit("sevenine"), 9);
+    fn test_to_text() {
+        let lexicon = DigitLexicon::new(&[("one", 1), ("two", 2), ("six", 6)]);
+        assert_eq!(
+            to_text(&explain("xtwone\nbptwone4six\nabc", &lexicon)),
+            "1: x[tw{o]ne} => 21 [overlap]
+  first: word \"two\" = 2 at 1..4
+  last:  word \"one\" = 1 at 3..6
+  overlap: word \"two\" = 2 at 1..4 with word \"one\" = 1 at 3..6
+2: bp[two]ne4{six} => 26 [overlap]
+  first: word \"two\" = 2 at 2..5
+  last:  word \"six\" = 6 at 8..11
+  overlap: word \"two\" = 2 at 2..5 with word \"one\" = 1 at 4..7
+3: abc => no digits
+  first: none
+  last:  none
+"
+        );
     }
 
     #[test]
-    fn test_first_digit() {
-        assert_eq!(first_digit("two1nine"), 2);
-        assert_eq!(first_digit("eightwothree"), 8);
-        assert_eq!(first_digit("abcone2threexyz"), 1);
-        assert_eq!(first_digit("xtwone3four"), 2);
-        assert_eq!(first_digit("4nineeightseven2"), 4);
-        assert_eq!(first_digit("zoneight234"), 1);
-        assert_eq!(first_digit("7pqrstsixteen"), 7);
-        assert_eq!(first_digit("twone"), 2);
-        assert_eq!(first_digit("sevenine"), 7);
-    }
-
-    #[test]
-    fn test_string_to_digit() {
-        assert_eq!(string_to_digit("one"), 1);
-        assert_eq!(string_to_digit("two"), 2);
-        assert_eq!(string_to_digit("three"), 3);
-        assert_eq!(string_to_digit("four"), 4);
-        assert_eq!(string_to_digit("five"), 5);
-        assert_eq!(string_to_digit("six"), 6);
-        assert_eq!(string_to_digit("seven"), 7);
-        assert_eq!(string_to_digit("eight"), 8);
-        assert_eq!(string_to_digit("nine"), 9);
+    fn test_to_json() {
+        let lexicon = DigitLexicon::new(&[("one", 1), ("two", 2)]);
+        assert_eq!(
+            to_json(&explain("twone\n7", &lexicon)),
+            "[{\"line\":1,\"text\":\"twone\",\"value\":21,\
+             \"first\":{\"kind\":\"word\",\"text\":\"two\",\"value\":2,\"start\":0,\"end\":3},\
+             \"last\":{\"kind\":\"word\",\"text\":\"one\",\"value\":1,\"start\":2,\"end\":5},\
+             \"overlaps\":[[\
+             {\"kind\":\"word\",\"text\":\"two\",\"value\":2,\"start\":0,\"end\":3},\
+             {\"kind\":\"word\",\"text\":\"one\",\"value\":1,\"start\":2,\"end\":5}]]},\
+             {\"line\":2,\"text\":\"7\",\"value\":77,\
+             \"first\":{\"kind\":\"digit\",\"text\":\"7\",\"value\":7,\"start\":0,\"end\":1},\
+             \"last\":{\"kind\":\"digit\",\"text\":\"7\",\"value\":7,\"start\":0,\"end\":1},\
+             \"overlaps\":[]}]"
+        );
+        assert_eq!(
+            to_json(&explain("abc", &lexicon)),
+            "[{\"line\":1,\"text\":\"abc\",\"value\":null,\"first\":null,\"last\":null,\"overlaps\":[]}]"
+        );
     }
 }
 
 fn main() -> std::io::Result<()> {
-    let mut file = File::open("input.txt")?;
-    let mut input = String::new();
-    file.read_to_string(&mut input)?;
-    let t1_sum = task1::handle_input(&input);
-    println!("Task1 answer: {t1_sum}");
-
-    let t2_sum = task2::handle_input(&input);
-    println!("Task2 answer: {t2_sum}");
+    let full_width = std::env::args().any(|a| a == "--full-width");
+    let policy = match std::env::args().find_map(|a| a.strip_prefix("--missing=").map(String::from))
+    {
+        Some(p) => Policy::from_str(&p).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unknown missing-digits policy {p:?}, expected skip, zero or error"),
+            )
+        })?,
+        None => Policy::Error,
+    };
+    let threads =
+        match std::env::args().find_map(|a| a.strip_prefix("--threads=").map(String::from)) {
+            Some(t) => t.parse::<usize>().map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("invalid thread count {t:?}: {e}"),
+                )
+            })?,
+            None => 1,
+        };
+    let lexicons = [task1::lexicon(), task2::lexicon()].map(|l| {
+        if full_width {
+            l.with_full_width_digits()
+        } else {
+            l
+        }
+    });
+
+    if std::env::args().any(|a| a == "--explain" || a == "--explain-json") {
+        let mut file = File::open("input.txt")?;
+        let mut input = String::new();
+        file.read_to_string(&mut input)?;
+        let explanations = explain::explain(&input, &lexicons[1]);
+        if std::env::args().any(|a| a == "--explain-json") {
+            println!("{}", explain::to_json(&explanations));
+        } else {
+            print!("{}", explain::to_text(&explanations));
+        }
+        return Ok(());
+    }
+
+    for (name, lexicon) in [("Task1", &lexicons[0]), ("Task2", &lexicons[1])] {
+        let reader = BufReader::new(File::open("input.txt")?);
+        let calibration = task1::calibrate_reader(reader, lexicon, policy, threads, 65536)?;
+        println!("{name} answer: {}", calibration.sum);
+        if calibration.affected != 0 {
+            println!("{name} lines without digits: {}", calibration.affected);
+        }
+    }
 
     Ok(())
 }
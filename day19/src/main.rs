@@ -0,0 +1,467 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+    println!("Task2 answer: {}", task2::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    use std::collections::HashMap;
+
+    pub fn handle_input(input: &str) -> u64 {
+        let (workflows, parts) = input.split_once("\n\n").unwrap();
+        let workflows = parse_workflows(workflows);
+        parts
+            .lines()
+            .map(Part::from_str)
+            .filter(|p| is_accepted(p, &workflows))
+            .map(|p| p.ratings.iter().sum::<u64>())
+            .sum()
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}"
+            ),
+            19114
+        );
+    }
+
+    fn is_accepted(part: &Part, workflows: &HashMap<String, Workflow>) -> bool {
+        let mut current = &workflows["in"];
+        loop {
+            match current.next(part) {
+                Target::Accept => return true,
+                Target::Reject => return false,
+                Target::Workflow(name) => current = &workflows[name],
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_accepted() {
+        let workflows = parse_workflows(
+            "in{s<1351:px,A}
+px{a<2006:A,R}",
+        );
+        assert!(is_accepted(
+            &Part::from_str("{x=1,m=1,a=1,s=1}"),
+            &workflows
+        ));
+        assert!(!is_accepted(
+            &Part::from_str("{x=1,m=1,a=2006,s=1}"),
+            &workflows
+        ));
+        assert!(is_accepted(
+            &Part::from_str("{x=1,m=1,a=2006,s=1351}"),
+            &workflows
+        ));
+    }
+
+    pub fn category_index(category: char) -> usize {
+        match category {
+            'x' => 0,
+            'm' => 1,
+            'a' => 2,
+            's' => 3,
+            _ => unreachable!(),
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Part {
+        ratings: [u64; 4],
+    }
+
+    impl Part {
+        fn from_str(input: &str) -> Self {
+            let mut ratings = [0; 4];
+            for rating in input.trim().trim_matches(['{', '}']).split(",") {
+                let (category, value) = rating.split_once("=").unwrap();
+                ratings[category_index(category.chars().next().unwrap())] =
+                    value.parse::<u64>().unwrap();
+            }
+            Self { ratings }
+        }
+    }
+
+    #[test]
+    fn test_parse_part() {
+        assert_eq!(
+            Part::from_str("{x=787,m=2655,a=1222,s=2876}"),
+            Part {
+                ratings: [787, 2655, 1222, 2876]
+            }
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Target {
+        Accept,
+        Reject,
+        Workflow(String),
+    }
+
+    impl Target {
+        fn from_str(input: &str) -> Self {
+            match input {
+                "A" => Target::Accept,
+                "R" => Target::Reject,
+                name => Target::Workflow(name.to_string()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Condition {
+        Less,
+        Greater,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Check {
+        pub category: usize,
+        pub condition: Condition,
+        pub value: u64,
+    }
+
+    impl Check {
+        fn from_str(input: &str) -> Self {
+            let category = category_index(input.chars().next().unwrap());
+            let condition = match input.chars().nth(1).unwrap() {
+                '<' => Condition::Less,
+                '>' => Condition::Greater,
+                _ => unreachable!(),
+            };
+            Self {
+                category,
+                condition,
+                value: input[2..].parse::<u64>().unwrap(),
+            }
+        }
+
+        fn matches(&self, part: &Part) -> bool {
+            let rating = part.ratings[self.category];
+            match self.condition {
+                Condition::Less => rating < self.value,
+                Condition::Greater => rating > self.value,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Rule {
+        pub check: Option<Check>,
+        pub target: Target,
+    }
+
+    impl Rule {
+        fn from_str(input: &str) -> Self {
+            match input.split_once(":") {
+                Some((check, target)) => Self {
+                    check: Some(Check::from_str(check)),
+                    target: Target::from_str(target),
+                },
+                None => Self {
+                    check: None,
+                    target: Target::from_str(input),
+                },
+            }
+        }
+
+        fn matches(&self, part: &Part) -> bool {
+            match &self.check {
+                Some(check) => check.matches(part),
+                None => true,
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_rule() {
+        assert_eq!(
+            Rule::from_str("a<2006:qkq"),
+            Rule {
+                check: Some(Check {
+                    category: 2,
+                    condition: Condition::Less,
+                    value: 2006
+                }),
+                target: Target::Workflow("qkq".to_string())
+            }
+        );
+        assert_eq!(
+            Rule::from_str("m>2090:A"),
+            Rule {
+                check: Some(Check {
+                    category: 1,
+                    condition: Condition::Greater,
+                    value: 2090
+                }),
+                target: Target::Accept
+            }
+        );
+        assert_eq!(
+            Rule::from_str("R"),
+            Rule {
+                check: None,
+                target: Target::Reject
+            }
+        );
+    }
+
+    pub struct Workflow {
+        pub rules: Vec<Rule>,
+    }
+
+    impl Workflow {
+        fn from_str(input: &str) -> Self {
+            Self {
+                rules: input.split(",").map(Rule::from_str).collect(),
+            }
+        }
+
+        fn next(&self, part: &Part) -> &Target {
+            for r in &self.rules {
+                if r.matches(part) {
+                    return &r.target;
+                }
+            }
+            unreachable!();
+        }
+    }
+
+    pub fn parse_workflows(input: &str) -> HashMap<String, Workflow> {
+        let mut results = HashMap::new();
+        for l in input.lines() {
+            let (name, rules) = l.trim().trim_end_matches('}').split_once("{").unwrap();
+            results.insert(name.to_string(), Workflow::from_str(rules));
+        }
+        results
+    }
+
+    #[test]
+    fn test_workflow_next() {
+        let workflows = parse_workflows("px{a<2006:qkq,m>2090:A,rfg}");
+        let px = &workflows["px"];
+        assert_eq!(px.rules.len(), 3);
+        assert_eq!(
+            px.next(&Part::from_str("{x=1,m=1,a=1,s=1}")),
+            &Target::Workflow("qkq".to_string())
+        );
+        assert_eq!(
+            px.next(&Part::from_str("{x=1,m=2091,a=2006,s=1}")),
+            &Target::Accept
+        );
+        assert_eq!(
+            px.next(&Part::from_str("{x=1,m=2090,a=2006,s=1}")),
+            &Target::Workflow("rfg".to_string())
+        );
+    }
+}
+
+mod task2 {
+    use crate::task1::{parse_workflows, Check, Condition, Target, Workflow};
+    use std::collections::HashMap;
+
+    pub fn handle_input(input: &str) -> u64 {
+        let (workflows, _) = input.split_once("\n\n").unwrap();
+        let workflows = parse_workflows(workflows);
+        count_accepted(
+            &Target::Workflow("in".to_string()),
+            Parts::new(PointRange::new(1, 4000)),
+            &workflows,
+        )
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}"
+            ),
+            167409079868000
+        );
+    }
+
+    fn count_accepted(target: &Target, parts: Parts, workflows: &HashMap<String, Workflow>) -> u64 {
+        match target {
+            Target::Accept => parts.combinations(),
+            Target::Reject => 0,
+            Target::Workflow(name) => workflows[name].count_accepted(parts, workflows),
+        }
+    }
+
+    impl Workflow {
+        fn count_accepted(&self, parts: Parts, workflows: &HashMap<String, Workflow>) -> u64 {
+            let mut count = 0;
+            let mut unmatched = parts;
+            for r in &self.rules {
+                match &r.check {
+                    Some(check) => {
+                        let (u, m) = check.split_parts(unmatched);
+                        unmatched = u;
+                        count += count_accepted(&r.target, m, workflows);
+                    }
+                    None => return count + count_accepted(&r.target, unmatched, workflows),
+                }
+            }
+            unreachable!();
+        }
+    }
+
+    impl Check {
+        fn split_parts(&self, parts: Parts) -> (Parts, Parts) {
+            let range = parts.ranges[self.category];
+            let (unmatched, matched) = match self.condition {
+                Condition::Less => {
+                    let (below, above) = range.split_at(self.value);
+                    (above, below)
+                }
+                Condition::Greater => range.split_at(self.value + 1),
+            };
+            (
+                parts.with_range(self.category, unmatched),
+                parts.with_range(self.category, matched),
+            )
+        }
+    }
+
+    #[test]
+    fn test_split_parts() {
+        let check = Check {
+            category: 2,
+            condition: Condition::Less,
+            value: 2006,
+        };
+        let (unmatched, matched) = check.split_parts(Parts::new(PointRange::new(1, 4000)));
+        assert_eq!(unmatched.ranges[2], PointRange::new(2006, 1995));
+        assert_eq!(matched.ranges[2], PointRange::new(1, 2005));
+        assert_eq!(matched.ranges[0], PointRange::new(1, 4000));
+
+        let check = Check {
+            category: 1,
+            condition: Condition::Greater,
+            value: 2090,
+        };
+        let (unmatched, matched) = check.split_parts(Parts::new(PointRange::new(1, 4000)));
+        assert_eq!(unmatched.ranges[1], PointRange::new(1, 2090));
+        assert_eq!(matched.ranges[1], PointRange::new(2091, 1910));
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    struct Parts {
+        ranges: [PointRange; 4],
+    }
+
+    impl Parts {
+        fn new(range: PointRange) -> Self {
+            Self { ranges: [range; 4] }
+        }
+
+        fn with_range(&self, category: usize, range: PointRange) -> Self {
+            let mut ranges = self.ranges;
+            ranges[category] = range;
+            Self { ranges }
+        }
+
+        fn combinations(&self) -> u64 {
+            self.ranges.iter().map(|r| r.length).product()
+        }
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            Parts::new(PointRange::new(1, 4000)).combinations(),
+            256000000000000
+        );
+        assert_eq!(
+            Parts::new(PointRange::new(1, 4000))
+                .with_range(3, PointRange::new(5, 0))
+                .combinations(),
+            0
+        );
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    struct PointRange {
+        start: u64,
+        length: u64,
+    }
+
+    impl PointRange {
+        fn new(start: u64, length: u64) -> Self {
+            Self { start, length }
+        }
+
+        fn end(&self) -> u64 {
+            self.start + self.length
+        }
+
+        fn split_at(&self, point: u64) -> (PointRange, PointRange) {
+            let middle = point.clamp(self.start, self.end());
+            (
+                PointRange::new(self.start, middle - self.start),
+                PointRange::new(middle, self.end() - middle),
+            )
+        }
+    }
+
+    #[test]
+    fn test_split_at() {
+        assert_eq!(
+            PointRange::new(8, 2).split_at(0),
+            (PointRange::new(8, 0), PointRange::new(8, 2))
+        );
+        assert_eq!(
+            PointRange::new(8, 2).split_at(9),
+            (PointRange::new(8, 1), PointRange::new(9, 1))
+        );
+        assert_eq!(
+            PointRange::new(8, 2).split_at(10),
+            (PointRange::new(8, 2), PointRange::new(10, 0))
+        );
+        assert_eq!(
+            PointRange::new(8, 2).split_at(20),
+            (PointRange::new(8, 2), PointRange::new(10, 0))
+        );
+    }
+}
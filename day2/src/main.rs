@@ -1,80 +1,416 @@
 use std::fs::File;
 use std::io::prelude::*;
+use task1::Bag;
 
 fn main() -> std::io::Result<()> {
+    let invalid = |e: task1::ParseError| std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+    let bag = if let Some(spec) =
+        std::env::args().find_map(|a| a.strip_prefix("--bag=").map(String::from))
+    {
+        Bag::from_str(&spec).map_err(invalid)?
+    } else if let Some(path) =
+        std::env::args().find_map(|a| a.strip_prefix("--bag-file=").map(String::from))
+    {
+        let mut spec = String::new();
+        File::open(path)?.read_to_string(&mut spec)?;
+        Bag::from_str(&spec).map_err(invalid)?
+    } else {
+        Bag::default()
+    };
+
     let mut file = File::open("input.txt")?;
     let mut input = String::new();
     file.read_to_string(&mut input)?;
 
-    let t1_sum = task1::handle_input(&input);
+    let games = task1::parse_games(&input).map_err(invalid)?;
+
+    let positional: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with("--"))
+        .collect();
+    let subcommand = positional.first().map(String::as_str);
+
+    if subcommand == Some("query") {
+        let expression = positional.get(1).map(String::as_str).unwrap_or_default();
+        let query = query::Query::from_str(expression)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        print!("{}", query::run(&query, &games, &bag));
+        return Ok(());
+    }
+
+    if subcommand == Some("estimate") {
+        let limit =
+            match std::env::args().find_map(|a| a.strip_prefix("--limit=").map(String::from)) {
+                Some(l) => l.parse::<u32>().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("invalid bag size limit {l:?}: {e}"),
+                    )
+                })?,
+                None => 100,
+            };
+        match estimate::estimate_games(&games, limit) {
+            Some(estimate) => {
+                println!("Estimated bag: {}", estimate.bag);
+                for (colour, interval) in &estimate.intervals {
+                    println!(
+                        "{colour}: {} (95% interval {}..={})",
+                        estimate.bag.count(colour),
+                        interval.low,
+                        interval.high
+                    );
+                }
+                println!("Log-likelihood: {:.3}", estimate.log_likelihood);
+                if estimate.at_limit {
+                    println!("Estimate reached the size limit of {limit} cubes");
+                }
+            }
+            None => println!("No bag of at most {limit} cubes fits the draws"),
+        }
+        return Ok(());
+    }
+
+    let t1_sum = task1::handle_input(&games, &bag);
     println!("Task1 answer: {t1_sum}");
 
-    let t2_sum = task2::handle_input(&input);
+    let t2_sum = task2::handle_input(&games, &bag);
     println!("Task2 answer: {t2_sum}");
 
     Ok(())
 }
 
 mod task1 {
-    use std::collections::HashMap;
+    use std::collections::BTreeMap;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq)]
+    pub enum ParseError {
+        MissingColon(String),
+        InvalidId(String),
+        InvalidCubes(String),
+    }
 
-    pub fn handle_input(input: &str) -> u32 {
-        let mut ret = 0;
-        for l in input.lines() {
-            ret += line_value(l);
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ParseError::MissingColon(s) => write!(f, "missing ':' in {s:?}"),
+                ParseError::InvalidId(s) => write!(f, "invalid game id {s:?}"),
+                ParseError::InvalidCubes(s) => write!(f, "invalid cubes {s:?}"),
+            }
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    #[derive(Debug, PartialEq)]
+    pub struct Cubes {
+        pub count: u32,
+        pub colour: String,
+    }
+
+    impl Cubes {
+        fn from_str(input: &str) -> Result<Self, ParseError> {
+            let invalid = || ParseError::InvalidCubes(input.to_string());
+            let (count, colour) = input.trim().split_once(" ").ok_or_else(invalid)?;
+            let colour = colour.trim();
+            if colour.is_empty() || colour.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+            Ok(Self {
+                count: count.parse::<u32>().map_err(|_| invalid())?,
+                colour: colour.to_string(),
+            })
+        }
+    }
+
+    impl fmt::Display for Cubes {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} {}", self.count, self.colour)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Draw {
+        pub cubes: Vec<Cubes>,
+    }
+
+    impl Draw {
+        pub fn from_str(input: &str) -> Result<Self, ParseError> {
+            Ok(Self {
+                cubes: input
+                    .split(",")
+                    .map(Cubes::from_str)
+                    .collect::<Result<_, _>>()?,
+            })
+        }
+
+        pub fn count(&self, colour: &str) -> u32 {
+            self.cubes
+                .iter()
+                .filter(|c| c.colour == colour)
+                .map(|c| c.count)
+                .sum()
+        }
+    }
+
+    impl fmt::Display for Draw {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let cubes: Vec<String> = self.cubes.iter().map(|c| c.to_string()).collect();
+            write!(f, "{}", cubes.join(", "))
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct Bag {
+        pub cubes: BTreeMap<String, u32>,
+    }
+
+    impl Bag {
+        pub fn new() -> Self {
+            Self {
+                cubes: BTreeMap::new(),
+            }
+        }
+
+        pub fn from_str(input: &str) -> Result<Self, ParseError> {
+            let mut bag = Self::new();
+            for spec in input.split([',', '\n']).filter(|s| !s.trim().is_empty()) {
+                let cubes = Cubes::from_str(spec)?;
+                bag.add(&cubes.colour, cubes.count);
+            }
+            Ok(bag)
+        }
+
+        pub fn add(&mut self, colour: &str, count: u32) {
+            *self.cubes.entry(colour.to_string()).or_insert(0) += count;
+        }
+
+        pub fn count(&self, colour: &str) -> u32 {
+            self.cubes.get(colour).copied().unwrap_or(0)
+        }
+
+        pub fn colours(&self) -> impl Iterator<Item = &str> {
+            self.cubes.keys().map(|c| c.as_str())
+        }
+
+        pub fn contains(&self, draw: &Draw) -> bool {
+            draw.cubes
+                .iter()
+                .all(|c| draw.count(&c.colour) <= self.count(&c.colour))
+        }
+
+        pub fn power(&self) -> u64 {
+            self.cubes.values().map(|c| *c as u64).product()
+        }
+    }
+
+    impl Default for Bag {
+        fn default() -> Self {
+            Self::from_str("12 red, 13 green, 14 blue").unwrap()
+        }
+    }
+
+    impl fmt::Display for Bag {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let cubes: Vec<String> = self
+                .cubes
+                .iter()
+                .map(|(colour, count)| format!("{count} {colour}"))
+                .collect();
+            write!(f, "{}", cubes.join(", "))
         }
-        ret
+    }
+
+    #[test]
+    fn test_bag() {
+        let bag = Bag::from_str("12 red, 13 green\n14 blue\n").unwrap();
+        assert_eq!(bag, Bag::default());
+        assert_eq!(bag.count("green"), 13);
+        assert_eq!(bag.count("purple"), 0);
+        assert_eq!(
+            bag.colours().collect::<Vec<_>>(),
+            vec!["blue", "green", "red"]
+        );
+        assert_eq!(bag.to_string(), "14 blue, 13 green, 12 red");
+        assert_eq!(bag.power(), 2184);
+        assert_eq!(Bag::from_str("1 red, 2 red").unwrap().count("red"), 3);
+        assert_eq!(
+            Bag::from_str("1 red, many blue"),
+            Err(ParseError::InvalidCubes(" many blue".to_string()))
+        );
+
+        assert!(bag.contains(&Draw::from_str("12 red, 13 green").unwrap()));
+        assert!(!bag.contains(&Draw::from_str("6 red, 7 red").unwrap()));
+        assert!(!bag.contains(&Draw::from_str("1 purple").unwrap()));
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Game {
+        pub id: u32,
+        pub draws: Vec<Draw>,
+    }
+
+    impl Game {
+        pub fn from_str(line: &str) -> Result<Self, ParseError> {
+            let (header, draws) = line
+                .trim()
+                .split_once(":")
+                .ok_or_else(|| ParseError::MissingColon(line.to_string()))?;
+            Ok(Self {
+                id: game_index(header)?,
+                draws: draws
+                    .split(";")
+                    .map(Draw::from_str)
+                    .collect::<Result<_, _>>()?,
+            })
+        }
+    }
+
+    impl fmt::Display for Game {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let draws: Vec<String> = self.draws.iter().map(|d| d.to_string()).collect();
+            write!(f, "Game {}: {}", self.id, draws.join("; "))
+        }
+    }
+
+    pub fn parse_games(input: &str) -> Result<Vec<Game>, ParseError> {
+        input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(Game::from_str)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_game() {
+        assert_eq!(
+            Game::from_str("Game 1: 3 blue, 4 red; 2 green"),
+            Ok(Game {
+                id: 1,
+                draws: vec![
+                    Draw {
+                        cubes: vec![
+                            Cubes {
+                                count: 3,
+                                colour: "blue".to_string()
+                            },
+                            Cubes {
+                                count: 4,
+                                colour: "red".to_string()
+                            }
+                        ]
+                    },
+                    Draw {
+                        cubes: vec![Cubes {
+                            count: 2,
+                            colour: "green".to_string()
+                        }]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Game::from_str("Game 1 3 blue"),
+            Err(ParseError::MissingColon("Game 1 3 blue".to_string()))
+        );
+        assert_eq!(
+            Game::from_str("Gaem x: 3 blue"),
+            Err(ParseError::InvalidId("Gaem x".to_string()))
+        );
+        assert_eq!(
+            Game::from_str("Game 1: three blue"),
+            Err(ParseError::InvalidCubes(" three blue".to_string()))
+        );
+        assert_eq!(
+            Game::from_str("Game 1: 3"),
+            Err(ParseError::InvalidCubes(" 3".to_string()))
+        );
+        assert_eq!(
+            parse_games("Game 1: 3 blue\nGame 2: 3 dark blue"),
+            Err(ParseError::InvalidCubes(" 3 dark blue".to_string()))
+        );
+        assert!(Game::from_str("Game 1: 3 purple").is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        for (game, line) in parse_games(input).unwrap().iter().zip(input.lines()) {
+            assert_eq!(game.to_string(), line);
+        }
+        assert_eq!(
+            Game::from_str("  Game 7:3 blue ,4 red;2  green ")
+                .unwrap()
+                .to_string(),
+            "Game 7: 3 blue, 4 red; 2 green"
+        );
+    }
+
+    pub fn handle_input(games: &[Game], bag: &Bag) -> u32 {
+        games.iter().map(|g| game_value(g, bag)).sum()
     }
 
     #[test]
     fn test_handle_input() {
         assert_eq!(
             handle_input(
-                "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+                &parse_games(
+                    "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
         Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
         Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
         Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
         Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"
+                )
+                .unwrap(),
+                &Bag::default()
             ),
             8
         );
     }
 
-    fn line_value(line: &str) -> u32 {
-        let (index, game) = line.trim().split_once(":").unwrap();
-        if is_possible_game(game) {
-            game_index(index)
+    fn game_value(game: &Game, bag: &Bag) -> u32 {
+        if is_possible_game(game, bag) {
+            game.id
         } else {
             0
         }
     }
 
     #[test]
-    fn test_line_value() {
+    fn test_game_value() {
+        let value = |line| game_value(&Game::from_str(line).unwrap(), &Bag::default());
         assert_eq!(
-            line_value("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
+            value("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
             1
         );
         assert_eq!(
-            line_value("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
+            value("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
             2
         );
         assert_eq!(
-            line_value(
+            value(
                 "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
         "
             ),
             0
         );
         assert_eq!(
-            line_value(
+            value(
                 "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
         "
             ),
             0
         );
         assert_eq!(
-            line_value(
+            value(
                 "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
         "
             ),
@@ -82,197 +418,980 @@ mod task1 {
         );
     }
 
-    fn is_possible_game(game: &str) -> bool {
-        for reach in game.split(";") {
-            if !is_possible_reach(reach) {
-                return false;
-            }
-        }
-        true
+    pub fn is_possible_game(game: &Game, bag: &Bag) -> bool {
+        game.draws.iter().all(|d| bag.contains(d))
     }
 
     #[test]
     fn test_is_possible_game() {
-        assert_eq!(
-            is_possible_game("3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
-            true
-        );
-        assert_eq!(
-            is_possible_game("1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
-            true
-        );
-        assert_eq!(
-            is_possible_game("8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"),
-            false
-        );
-        assert_eq!(
-            is_possible_game("1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"),
-            false
-        );
-        assert_eq!(
-            is_possible_game("6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"),
-            true
-        );
-    }
+        let possible = |draws| {
+            is_possible_game(
+                &Game::from_str(&format!("Game 1: {draws}")).unwrap(),
+                &Bag::default(),
+            )
+        };
+        assert!(possible("3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"));
+        assert!(possible(
+            "1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"
+        ));
+        assert!(!possible(
+            "8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"
+        ));
+        assert!(!possible(
+            "1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"
+        ));
+        assert!(possible("6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"));
+        assert!(!possible("1 purple, 2 red"));
 
-    fn is_possible_reach(reach: &str) -> bool {
-        let color_map: HashMap<&str, u32> =
-            HashMap::from([("red", 12), ("green", 13), ("blue", 14)]);
-
-        for s in reach.split(",") {
-            let (val, color) = s.trim().split_once(" ").unwrap();
-            if color_map[color] < val.parse::<u32>().unwrap() {
-                return false;
-            }
-        }
-        true
+        let bag = Bag::from_str("2 red, 1 purple").unwrap();
+        let game = Game::from_str("Game 1: 1 purple, 2 red; 1 red").unwrap();
+        assert!(is_possible_game(&game, &bag));
     }
 
     #[test]
-    fn test_is_possible_reach() {
-        assert_eq!(is_possible_reach("3 blue"), true);
-        assert_eq!(is_possible_reach("12 red"), true);
-        assert_eq!(is_possible_reach("13 red"), false);
-        assert_eq!(is_possible_reach("13 green"), true);
-        assert_eq!(is_possible_reach("14 green"), false);
-        assert_eq!(is_possible_reach("14 blue"), true);
-        assert_eq!(is_possible_reach("15 blue"), false);
+    fn test_is_possible_draw() {
+        let possible = |draw| Bag::default().contains(&Draw::from_str(draw).unwrap());
+        assert!(possible("3 blue"));
+        assert!(possible("12 red"));
+        assert!(!possible("13 red"));
+        assert!(possible("13 green"));
+        assert!(!possible("14 green"));
+        assert!(possible("14 blue"));
+        assert!(!possible("15 blue"));
 
-        assert_eq!(is_possible_reach("3 blue, 4 red"), true);
-        assert_eq!(is_possible_reach("1 red, 2 green, 6 blue"), true);
-        assert_eq!(is_possible_reach("8 green, 6 blue, 20 red"), false);
+        assert!(possible("3 blue, 4 red"));
+        assert!(possible("1 red, 2 green, 6 blue"));
+        assert!(!possible("8 green, 6 blue, 20 red"));
     }
 
-    fn game_index(game_spec: &str) -> u32 {
-        let index_str = game_spec.split(" ").collect::<Vec<&str>>()[1];
-        index_str.parse::<u32>().unwrap()
+    fn game_index(game_spec: &str) -> Result<u32, ParseError> {
+        let invalid = || ParseError::InvalidId(game_spec.to_string());
+        let index_str = game_spec.trim().strip_prefix("Game ").ok_or_else(invalid)?;
+        index_str.trim().parse::<u32>().map_err(|_| invalid())
     }
 
     #[test]
     fn test_game_index() {
-        assert_eq!(game_index("Game 1"), 1);
-        assert_eq!(game_index("Game 10"), 10);
-        assert_eq!(game_index("Game 29"), 29);
+        assert_eq!(game_index("Game 1"), Ok(1));
+        assert_eq!(game_index("Game 10"), Ok(10));
+        assert_eq!(game_index("Game 29"), Ok(29));
+        assert_eq!(
+            game_index("Game"),
+            Err(ParseError::InvalidId("Game".to_string()))
+        );
     }
 }
 
 mod task2 {
-    use std::cmp::max;
-    use std::collections::HashMap;
+    use crate::task1::{Bag, Draw, Game};
 
-    pub fn handle_input(input: &str) -> u32 {
-        let mut ret = 0;
-        for l in input.lines() {
-            ret += line_value(l);
-        }
-        ret
+    pub fn handle_input(games: &[Game], bag: &Bag) -> u64 {
+        games.iter().map(|g| game_power(g, bag)).sum()
     }
 
     #[test]
     fn test_handle_input() {
+        use crate::task1::parse_games;
         assert_eq!(
             handle_input(
-                "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+                &parse_games(
+                    "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
         Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
         Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
         Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
         Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"
+                )
+                .unwrap(),
+                &Bag::default()
             ),
             2286
         );
     }
 
-    fn line_value(line: &str) -> u32 {
-        let (_, game) = line.trim().split_once(":").unwrap();
-        let (r, g, b) = game_values(game);
-        r * g * b
+    fn game_power(game: &Game, bag: &Bag) -> u64 {
+        let mut minimum = game_values(game);
+        for colour in bag.colours() {
+            minimum.add(colour, 0);
+        }
+        minimum.power()
     }
 
     #[test]
-    fn test_line_value() {
+    fn test_game_power() {
+        let power = |line| game_power(&Game::from_str(line).unwrap(), &Bag::default());
         assert_eq!(
-            line_value("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
+            power("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
             48
         );
         assert_eq!(
-            line_value("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
+            power("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
             12
         );
         assert_eq!(
-            line_value("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"),
+            power("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"),
             1560
         );
         assert_eq!(
-            line_value("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"),
+            power("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"),
             630
         );
         assert_eq!(
-            line_value("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"),
+            power("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"),
             36
         );
+        assert_eq!(power("Game 6: 2 red, 3 green"), 0);
+        assert_eq!(power("Game 7: 2 red, 3 green, 4 blue; 5 purple"), 120);
     }
 
-    fn game_values(game: &str) -> (u32, u32, u32) {
-        let mut ret = (0, 0, 0);
-        for reach in game.split(";") {
-            let rgb = reach_values(reach);
-            ret = (max(rgb.0, ret.0), max(rgb.1, ret.1), max(rgb.2, ret.2));
+    pub fn game_values(game: &Game) -> Bag {
+        let mut ret = Bag::new();
+        for draw in &game.draws {
+            for (colour, count) in draw_values(draw).cubes {
+                let current = ret.count(&colour);
+                ret.add(&colour, count.saturating_sub(current));
+            }
         }
         ret
     }
 
     #[test]
     fn test_game_values() {
+        let values = |draws| game_values(&Game::from_str(&format!("Game 1: {draws}")).unwrap());
+        let bag = |spec| Bag::from_str(spec).unwrap();
+        assert_eq!(
+            values("3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
+            bag("4 red, 2 green, 6 blue")
+        );
+        assert_eq!(
+            values("1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
+            bag("1 red, 3 green, 4 blue")
+        );
+        assert_eq!(
+            values("8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"),
+            bag("20 red, 13 green, 6 blue")
+        );
+        assert_eq!(
+            values("1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"),
+            bag("14 red, 3 green, 15 blue")
+        );
+        assert_eq!(
+            values("6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"),
+            bag("6 red, 3 green, 2 blue")
+        );
+        assert_eq!(
+            values("2 purple, 1 teal; 5 purple"),
+            bag("5 purple, 1 teal")
+        );
+    }
+
+    fn draw_values(draw: &Draw) -> Bag {
+        let mut ret = Bag::new();
+        for cubes in &draw.cubes {
+            ret.add(&cubes.colour, cubes.count);
+        }
+        ret
+    }
+
+    #[test]
+    fn test_draw_values() {
+        let values = |draw| draw_values(&Draw::from_str(draw).unwrap());
+        let bag = |spec| Bag::from_str(spec).unwrap();
+        assert_eq!(values("3 blue, 4 red"), bag("4 red, 3 blue"));
+        assert_eq!(
+            values("1 red, 2 green, 6 blue"),
+            bag("1 red, 2 green, 6 blue")
+        );
+        assert_eq!(values("2 green"), bag("2 green"));
+        assert_eq!(
+            values("3 green, 15 blue, 14 red"),
+            bag("14 red, 3 green, 15 blue")
+        );
+        assert_eq!(values("2 blue, 1 red, 2 blue"), bag("1 red, 4 blue"));
+        assert_eq!(values("7 octarine"), bag("7 octarine"));
+    }
+}
+
+mod query {
+    use crate::task1::{is_possible_game, Bag, Game};
+    use crate::task2::game_values;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq)]
+    pub struct QueryError(String);
+
+    impl fmt::Display for QueryError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "invalid query: {}", self.0)
+        }
+    }
+
+    impl std::error::Error for QueryError {}
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Op {
+        Lt,
+        Le,
+        Gt,
+        Ge,
+        Eq,
+        Ne,
+    }
+
+    impl Op {
+        fn apply(&self, a: u64, b: u64) -> bool {
+            match self {
+                Op::Lt => a < b,
+                Op::Le => a <= b,
+                Op::Gt => a > b,
+                Op::Ge => a >= b,
+                Op::Eq => a == b,
+                Op::Ne => a != b,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone)]
+    enum Token {
+        Word(String),
+        Number(u64),
+        Op(Op),
+        Open,
+        Close,
+        Pipe,
+    }
+
+    fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+        let chars: Vec<char> = input.chars().collect();
+        let mut tokens = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            match c {
+                _ if c.is_whitespace() => i += 1,
+                '(' => {
+                    tokens.push(Token::Open);
+                    i += 1;
+                }
+                ')' => {
+                    tokens.push(Token::Close);
+                    i += 1;
+                }
+                '|' => {
+                    tokens.push(Token::Pipe);
+                    i += 1;
+                }
+                '<' | '>' | '=' | '!' => {
+                    let op = match (c, next == Some('=')) {
+                        ('<', false) => Op::Lt,
+                        ('<', true) => Op::Le,
+                        ('>', false) => Op::Gt,
+                        ('>', true) => Op::Ge,
+                        ('=', _) => Op::Eq,
+                        ('!', true) => Op::Ne,
+                        _ => return Err(QueryError(format!("unexpected {c:?}"))),
+                    };
+                    tokens.push(Token::Op(op));
+                    i += if next == Some('=') { 2 } else { 1 };
+                }
+                _ => {
+                    let start = i;
+                    while i < chars.len()
+                        && !chars[i].is_whitespace()
+                        && !"()|<>=!".contains(chars[i])
+                    {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    tokens.push(match word.parse::<u64>() {
+                        Ok(n) => Token::Number(n),
+                        Err(_) => Token::Word(word),
+                    });
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    #[test]
+    fn test_tokenize() {
         assert_eq!(
-            game_values("3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
-            (4, 2, 6)
+            tokenize("not(max(blue)>=10)|count"),
+            Ok(vec![
+                Token::Word("not".to_string()),
+                Token::Open,
+                Token::Word("max".to_string()),
+                Token::Open,
+                Token::Word("blue".to_string()),
+                Token::Close,
+                Token::Op(Op::Ge),
+                Token::Number(10),
+                Token::Close,
+                Token::Pipe,
+                Token::Word("count".to_string()),
+            ])
         );
         assert_eq!(
-            game_values("1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue"),
-            (1, 3, 4)
+            tokenize("draws == 3 and id != 2 or id = 1"),
+            Ok(vec![
+                Token::Word("draws".to_string()),
+                Token::Op(Op::Eq),
+                Token::Number(3),
+                Token::Word("and".to_string()),
+                Token::Word("id".to_string()),
+                Token::Op(Op::Ne),
+                Token::Number(2),
+                Token::Word("or".to_string()),
+                Token::Word("id".to_string()),
+                Token::Op(Op::Eq),
+                Token::Number(1),
+            ])
         );
+        assert!(tokenize("id ! 2").is_err());
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Metric {
+        Max(String),
+        Min(String),
+        Sum(String),
+        Draws,
+        Id,
+    }
+
+    impl Metric {
+        pub fn evaluate(&self, game: &Game) -> u64 {
+            match self {
+                Metric::Max(colour) => game_values(game).count(colour) as u64,
+                Metric::Min(colour) => game
+                    .draws
+                    .iter()
+                    .map(|d| d.count(colour) as u64)
+                    .min()
+                    .unwrap_or(0),
+                Metric::Sum(colour) => game.draws.iter().map(|d| d.count(colour) as u64).sum(),
+                Metric::Draws => game.draws.len() as u64,
+                Metric::Id => game.id as u64,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Predicate {
+        Compare(Metric, Op, u64),
+        Possible,
+        Not(Box<Predicate>),
+        And(Box<Predicate>, Box<Predicate>),
+        Or(Box<Predicate>, Box<Predicate>),
+    }
+
+    impl Predicate {
+        pub fn matches(&self, game: &Game, bag: &Bag) -> bool {
+            match self {
+                Predicate::Compare(metric, op, value) => op.apply(metric.evaluate(game), *value),
+                Predicate::Possible => is_possible_game(game, bag),
+                Predicate::Not(p) => !p.matches(game, bag),
+                Predicate::And(a, b) => a.matches(game, bag) && b.matches(game, bag),
+                Predicate::Or(a, b) => a.matches(game, bag) || b.matches(game, bag),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Aggregate {
+        Count,
+        Sum(Metric),
+        Avg(Metric),
+        Min(Metric),
+        Max(Metric),
+    }
+
+    impl Aggregate {
+        pub fn evaluate(&self, games: &[&Game]) -> Option<f64> {
+            let values = |metric: &Metric| -> Vec<u64> {
+                games.iter().map(|g| metric.evaluate(g)).collect()
+            };
+            match self {
+                Aggregate::Count => Some(games.len() as f64),
+                Aggregate::Sum(metric) => Some(values(metric).iter().sum::<u64>() as f64),
+                Aggregate::Avg(metric) => {
+                    let values = values(metric);
+                    if values.is_empty() {
+                        None
+                    } else {
+                        Some(values.iter().sum::<u64>() as f64 / values.len() as f64)
+                    }
+                }
+                Aggregate::Min(metric) => values(metric).into_iter().min().map(|v| v as f64),
+                Aggregate::Max(metric) => values(metric).into_iter().max().map(|v| v as f64),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Query {
+        pub filter: Option<Predicate>,
+        pub aggregate: Option<Aggregate>,
+    }
+
+    struct Parser {
+        tokens: Vec<Token>,
+        position: usize,
+    }
+
+    impl Parser {
+        fn peek(&self) -> Option<&Token> {
+            self.tokens.get(self.position)
+        }
+
+        fn next(&mut self) -> Option<Token> {
+            let token = self.tokens.get(self.position).cloned();
+            self.position += 1;
+            token
+        }
+
+        fn expect(&mut self, expected: Token) -> Result<(), QueryError> {
+            match self.next() {
+                Some(token) if token == expected => Ok(()),
+                other => Err(QueryError(format!(
+                    "expected {expected:?}, found {other:?}"
+                ))),
+            }
+        }
+
+        fn keyword(&mut self, word: &str) -> bool {
+            if self.peek() == Some(&Token::Word(word.to_string())) {
+                self.position += 1;
+                true
+            } else {
+                false
+            }
+        }
+
+        fn word(&mut self) -> Result<String, QueryError> {
+            match self.next() {
+                Some(Token::Word(word)) => Ok(word),
+                other => Err(QueryError(format!("expected a name, found {other:?}"))),
+            }
+        }
+
+        fn query(&mut self) -> Result<Query, QueryError> {
+            let filter = match self.peek() {
+                None | Some(Token::Pipe) => None,
+                _ => Some(self.predicate()?),
+            };
+            let aggregate = if self.peek() == Some(&Token::Pipe) {
+                self.position += 1;
+                Some(self.aggregate()?)
+            } else {
+                None
+            };
+            if let Some(token) = self.peek() {
+                return Err(QueryError(format!("unexpected {token:?}")));
+            }
+            Ok(Query { filter, aggregate })
+        }
+
+        fn predicate(&mut self) -> Result<Predicate, QueryError> {
+            let mut left = self.conjunction()?;
+            while self.keyword("or") {
+                left = Predicate::Or(Box::new(left), Box::new(self.conjunction()?));
+            }
+            Ok(left)
+        }
+
+        fn conjunction(&mut self) -> Result<Predicate, QueryError> {
+            let mut left = self.unary()?;
+            while self.keyword("and") {
+                left = Predicate::And(Box::new(left), Box::new(self.unary()?));
+            }
+            Ok(left)
+        }
+
+        fn unary(&mut self) -> Result<Predicate, QueryError> {
+            if self.keyword("not") {
+                return Ok(Predicate::Not(Box::new(self.unary()?)));
+            }
+            if self.keyword("possible") {
+                return Ok(Predicate::Possible);
+            }
+            if self.peek() == Some(&Token::Open) {
+                self.position += 1;
+                let inner = self.predicate()?;
+                self.expect(Token::Close)?;
+                return Ok(inner);
+            }
+            let metric = self.metric()?;
+            let op = match self.next() {
+                Some(Token::Op(op)) => op,
+                other => {
+                    return Err(QueryError(format!(
+                        "expected a comparison, found {other:?}"
+                    )))
+                }
+            };
+            match self.next() {
+                Some(Token::Number(value)) => Ok(Predicate::Compare(metric, op, value)),
+                other => Err(QueryError(format!("expected a number, found {other:?}"))),
+            }
+        }
+
+        fn metric(&mut self) -> Result<Metric, QueryError> {
+            let name = self.word()?;
+            let colour = |parser: &mut Parser| -> Result<String, QueryError> {
+                parser.expect(Token::Open)?;
+                let colour = parser.word()?;
+                parser.expect(Token::Close)?;
+                Ok(colour)
+            };
+            match name.as_str() {
+                "draws" => Ok(Metric::Draws),
+                "id" => Ok(Metric::Id),
+                "max" => Ok(Metric::Max(colour(self)?)),
+                "min" => Ok(Metric::Min(colour(self)?)),
+                "sum" => Ok(Metric::Sum(colour(self)?)),
+                _ => Err(QueryError(format!("unknown metric {name:?}"))),
+            }
+        }
+
+        fn aggregate(&mut self) -> Result<Aggregate, QueryError> {
+            let name = self.word()?;
+            if name == "count" {
+                return Ok(Aggregate::Count);
+            }
+            self.expect(Token::Open)?;
+            let metric = self.metric()?;
+            self.expect(Token::Close)?;
+            match name.as_str() {
+                "sum" => Ok(Aggregate::Sum(metric)),
+                "avg" => Ok(Aggregate::Avg(metric)),
+                "min" => Ok(Aggregate::Min(metric)),
+                "max" => Ok(Aggregate::Max(metric)),
+                _ => Err(QueryError(format!("unknown aggregation {name:?}"))),
+            }
+        }
+    }
+
+    impl Query {
+        pub fn from_str(input: &str) -> Result<Self, QueryError> {
+            Parser {
+                tokens: tokenize(input)?,
+                position: 0,
+            }
+            .query()
+        }
+
+        pub fn select<'a>(&self, games: &'a [Game], bag: &Bag) -> Vec<&'a Game> {
+            games
+                .iter()
+                .filter(|g| self.filter.as_ref().is_none_or(|p| p.matches(g, bag)))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_parse_query() {
         assert_eq!(
-            game_values("8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red"),
-            (20, 13, 6)
+            Query::from_str("max(blue) > 10"),
+            Ok(Query {
+                filter: Some(Predicate::Compare(
+                    Metric::Max("blue".to_string()),
+                    Op::Gt,
+                    10
+                )),
+                aggregate: None
+            })
         );
         assert_eq!(
-            game_values("1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red"),
-            (14, 3, 15)
+            Query::from_str("not possible or draws < 3 and id != 1 | avg(sum(red))"),
+            Ok(Query {
+                filter: Some(Predicate::Or(
+                    Box::new(Predicate::Not(Box::new(Predicate::Possible))),
+                    Box::new(Predicate::And(
+                        Box::new(Predicate::Compare(Metric::Draws, Op::Lt, 3)),
+                        Box::new(Predicate::Compare(Metric::Id, Op::Ne, 1))
+                    ))
+                )),
+                aggregate: Some(Aggregate::Avg(Metric::Sum("red".to_string())))
+            })
         );
         assert_eq!(
-            game_values("6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"),
-            (6, 3, 2)
+            Query::from_str("| count"),
+            Ok(Query {
+                filter: None,
+                aggregate: Some(Aggregate::Count)
+            })
         );
+        assert!(Query::from_str("max(blue)").is_err());
+        assert!(Query::from_str("max blue > 1").is_err());
+        assert!(Query::from_str("draws > lots").is_err());
+        assert!(Query::from_str("(draws > 1").is_err());
+        assert!(Query::from_str("draws > 1 | median(draws)").is_err());
+        assert!(Query::from_str("draws > 1 draws").is_err());
     }
 
-    fn reach_values(reach: &str) -> (u32, u32, u32) {
-        let mut color_values: HashMap<&str, u32> = HashMap::new();
-        for s in reach.split(",") {
-            let (val, color) = s.trim().split_once(" ").unwrap();
-            color_values.insert(color, val.parse::<u32>().unwrap());
+    pub fn run(query: &Query, games: &[Game], bag: &Bag) -> String {
+        let selected = query.select(games, bag);
+        match &query.aggregate {
+            Some(aggregate) => match aggregate.evaluate(&selected) {
+                Some(value) => format!("{value}\n"),
+                None => "none\n".to_string(),
+            },
+            None => selected.iter().map(|g| format!("{g}\n")).collect(),
         }
-        (
-            *color_values.get("red").unwrap_or(&0),
-            *color_values.get("green").unwrap_or(&0),
-            *color_values.get("blue").unwrap_or(&0),
+    }
+
+    #[test]
+    fn test_run() {
+        use crate::task1::parse_games;
+        let games = parse_games(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+        Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+        Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+        Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
         )
+        .unwrap();
+        let run = |query| run(&Query::from_str(query).unwrap(), &games, &Bag::default());
+        assert_eq!(
+            run("max(blue) > 10"),
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red\n"
+        );
+        assert_eq!(run("possible | sum(id)"), "8\n");
+        assert_eq!(run("| count"), "5\n");
+        assert_eq!(run("| avg(draws)"), "2.8\n");
+        assert_eq!(run("not possible and sum(red) >= 20 | count"), "2\n");
+        assert_eq!(run("min(green) == 0 | max(id)"), "1\n");
+        assert_eq!(run("id > 5 | avg(draws)"), "none\n");
+        assert_eq!(run("id > 5"), "");
+        assert_eq!(
+            run("(id = 1 or id = 2) and draws = 3 | max(max(blue))"),
+            "6\n"
+        );
+    }
+}
+
+mod estimate {
+    use crate::task1::{Bag, Draw, Game};
+    use std::collections::BTreeMap;
+
+    const CHI_SQUARED_95: f64 = 3.841;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Interval {
+        pub low: u32,
+        pub high: u32,
+    }
+
+    #[derive(Debug)]
+    pub struct Estimate {
+        pub bag: Bag,
+        pub log_likelihood: f64,
+        pub intervals: BTreeMap<String, Interval>,
+        pub at_limit: bool,
+    }
+
+    struct Observations {
+        colours: Vec<String>,
+        counts: Vec<Vec<u32>>,
+        handfuls: Vec<u32>,
+        ln_factorials: Vec<f64>,
+        limit: u32,
+    }
+
+    impl Observations {
+        fn new(draws: &[&Draw], limit: u32) -> Self {
+            let mut drawn = Bag::new();
+            for draw in draws {
+                for cubes in &draw.cubes {
+                    drawn.add(&cubes.colour, cubes.count);
+                }
+            }
+            let colours: Vec<String> = drawn
+                .colours()
+                .filter(|c| drawn.count(c) > 0)
+                .map(String::from)
+                .collect();
+            let counts = colours
+                .iter()
+                .map(|c| draws.iter().map(|d| d.count(c)).collect())
+                .collect();
+            let handfuls = draws
+                .iter()
+                .map(|d| d.cubes.iter().map(|c| c.count).sum())
+                .collect();
+            let mut ln_factorials = vec![0.0];
+            for n in 1..=limit {
+                ln_factorials.push(ln_factorials[n as usize - 1] + (n as f64).ln());
+            }
+            Self {
+                colours,
+                counts,
+                handfuls,
+                ln_factorials,
+                limit,
+            }
+        }
+
+        fn ln_choose(&self, n: u32, k: u32) -> f64 {
+            let f = &self.ln_factorials;
+            f[n as usize] - f[k as usize] - f[(n - k) as usize]
+        }
+
+        fn minimum(&self, colour: usize) -> u32 {
+            self.counts[colour].iter().copied().max().unwrap_or(0)
+        }
+
+        fn gain(&self, colour: usize, n: u32) -> f64 {
+            self.counts[colour]
+                .iter()
+                .map(|x| ((n + 1) as f64 / (n + 1 - x) as f64).ln())
+                .sum()
+        }
+
+        fn colour_likelihood(&self, colour: usize, n: u32) -> f64 {
+            self.counts[colour]
+                .iter()
+                .map(|x| self.ln_choose(n, *x))
+                .sum()
+        }
+
+        fn total_likelihood(&self, total: u32) -> f64 {
+            self.handfuls
+                .iter()
+                .map(|k| self.ln_choose(total, *k))
+                .sum()
+        }
+
+        fn profile(&self, fixed: Option<(usize, u32)>) -> Option<(f64, Vec<u32>)> {
+            let mut counts: Vec<u32> = (0..self.colours.len()).map(|c| self.minimum(c)).collect();
+            if let Some((colour, n)) = fixed {
+                if n < counts[colour] {
+                    return None;
+                }
+                counts[colour] = n;
+            }
+            let mut total: u32 = counts.iter().sum();
+            if total > self.limit {
+                return None;
+            }
+            let mut likelihood: f64 = counts
+                .iter()
+                .enumerate()
+                .map(|(c, n)| self.colour_likelihood(c, *n))
+                .sum();
+            let mut gains: Vec<Option<f64>> = counts
+                .iter()
+                .enumerate()
+                .map(|(c, n)| match fixed {
+                    Some((f, _)) if f == c => None,
+                    _ => Some(self.gain(c, *n)),
+                })
+                .collect();
+
+            let mut best: Option<(f64, Vec<u32>)> = None;
+            loop {
+                let candidate = likelihood - self.total_likelihood(total);
+                if best.as_ref().is_none_or(|(b, _)| candidate > *b) {
+                    best = Some((candidate, counts.clone()));
+                }
+                if total == self.limit {
+                    break;
+                }
+                let Some((colour, gain)) = gains
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(c, g)| g.map(|g| (c, g)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                else {
+                    break;
+                };
+                counts[colour] += 1;
+                total += 1;
+                likelihood += gain;
+                gains[colour] = Some(self.gain(colour, counts[colour]));
+            }
+            best
+        }
+
+        fn interval(&self, colour: usize, best: f64) -> Interval {
+            let inside: Vec<u32> = (self.minimum(colour)..=self.limit)
+                .filter(|n| {
+                    self.profile(Some((colour, *n)))
+                        .is_some_and(|(l, _)| 2.0 * (best - l) <= CHI_SQUARED_95)
+                })
+                .collect();
+            Interval {
+                low: inside[0],
+                high: inside[inside.len() - 1],
+            }
+        }
+    }
+
+    pub fn estimate_bag(draws: &[&Draw], limit: u32) -> Option<Estimate> {
+        let observations = Observations::new(draws, limit);
+        if observations.colours.is_empty() {
+            return None;
+        }
+        let (log_likelihood, counts) = observations.profile(None)?;
+        let mut bag = Bag::new();
+        let mut intervals = BTreeMap::new();
+        for (c, colour) in observations.colours.iter().enumerate() {
+            bag.add(colour, counts[c]);
+            intervals.insert(colour.clone(), observations.interval(c, log_likelihood));
+        }
+        Some(Estimate {
+            bag,
+            log_likelihood,
+            intervals,
+            at_limit: counts.iter().sum::<u32>() == limit,
+        })
+    }
+
+    pub fn estimate_games(games: &[Game], limit: u32) -> Option<Estimate> {
+        let draws: Vec<&Draw> = games.iter().flat_map(|g| &g.draws).collect();
+        estimate_bag(&draws, limit)
+    }
+
+    #[cfg(test)]
+    fn shuffle<T>(state: &mut u64, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            items.swap(i, (*state % (i as u64 + 1)) as usize);
+        }
+    }
+
+    #[cfg(test)]
+    fn synthetic_draws(bag: &Bag, handful: usize, draws: usize, seed: u64) -> Vec<Draw> {
+        use crate::task1::Cubes;
+        let mut state = seed.max(1);
+        let mut cubes: Vec<&str> = bag
+            .colours()
+            .flat_map(|c| std::iter::repeat_n(c, bag.count(c) as usize))
+            .collect();
+        (0..draws)
+            .map(|_| {
+                shuffle(&mut state, &mut cubes);
+                let mut drawn = Bag::new();
+                for colour in &cubes[..handful] {
+                    drawn.add(colour, 1);
+                }
+                Draw {
+                    cubes: drawn
+                        .cubes
+                        .into_iter()
+                        .map(|(colour, count)| Cubes { count, colour })
+                        .collect(),
+                }
+            })
+            .collect()
     }
 
     #[test]
-    fn test_reach_values() {
-        assert_eq!(reach_values("3 blue, 4 red"), (4, 0, 3));
-        assert_eq!(reach_values("1 red, 2 green, 6 blue"), (1, 2, 6));
-        assert_eq!(reach_values("2 green"), (0, 2, 0));
-        assert_eq!(reach_values("1 blue, 2 green"), (0, 2, 1));
-        assert_eq!(reach_values("3 green, 4 blue, 1 red"), (1, 3, 4));
-        assert_eq!(reach_values("1 green, 1 blue"), (0, 1, 1));
-        assert_eq!(reach_values("8 green, 6 blue, 20 red"), (20, 8, 6));
-        assert_eq!(reach_values("5 blue, 4 red, 13 green"), (4, 13, 5));
-        assert_eq!(reach_values("5 green, 1 red"), (1, 5, 0));
-        assert_eq!(reach_values("1 green, 3 red, 6 blue"), (3, 1, 6));
-        assert_eq!(reach_values("3 green, 6 red"), (6, 3, 0));
-        assert_eq!(reach_values("3 green, 15 blue, 14 red"), (14, 3, 15));
-        assert_eq!(reach_values("6 red, 1 blue, 3 green"), (6, 3, 1));
-        assert_eq!(reach_values("2 blue, 1 red, 2 green"), (1, 2, 2));
+    fn test_ln_choose() {
+        let observations = Observations::new(&[], 10);
+        assert!((observations.ln_choose(10, 3) - 120f64.ln()).abs() < 1e-9);
+        assert_eq!(observations.ln_choose(5, 0), 0.0);
+        assert!(observations.ln_choose(5, 5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_profile_matches_brute_force() {
+        let draws: Vec<Draw> = [
+            "3 red, 1 blue",
+            "2 red, 2 blue",
+            "4 red",
+            "1 red, 2 blue, 1 green",
+        ]
+        .iter()
+        .map(|d| Draw::from_str(d).unwrap())
+        .collect();
+        let draws: Vec<&Draw> = draws.iter().collect();
+        let observations = Observations::new(&draws, 20);
+        let (best, counts) = observations.profile(None).unwrap();
+
+        let mut brute = f64::NEG_INFINITY;
+        for blue in 2..=20 {
+            for green in 1..=20 {
+                for red in 4..=20 {
+                    if blue + green + red > 20 {
+                        continue;
+                    }
+                    let n = [blue, green, red];
+                    let likelihood: f64 = (0..3)
+                        .map(|c| observations.colour_likelihood(c, n[c]))
+                        .sum::<f64>()
+                        - observations.total_likelihood(blue + green + red);
+                    brute = brute.max(likelihood);
+                }
+            }
+        }
+        assert!((best - brute).abs() < 1e-9);
+        assert_eq!(counts.len(), 3);
+    }
+
+    #[test]
+    fn test_estimate_exhaustive_draws() {
+        let draws: Vec<Draw> = (0..10)
+            .map(|_| Draw::from_str("5 red, 3 green").unwrap())
+            .collect();
+        let draws: Vec<&Draw> = draws.iter().collect();
+        let estimate = estimate_bag(&draws, 50).unwrap();
+        assert_eq!(estimate.bag, Bag::from_str("5 red, 3 green").unwrap());
+        assert!(estimate.log_likelihood.abs() < 1e-9);
+        assert!(!estimate.at_limit);
+        assert_eq!(estimate.intervals["red"], Interval { low: 5, high: 5 });
+        assert_eq!(estimate.intervals["green"], Interval { low: 3, high: 3 });
+        assert!(estimate_bag(&[], 50).is_none());
+        assert!(estimate_bag(&draws, 7).is_none());
+        assert!(estimate_bag(&draws, 8).unwrap().at_limit);
+    }
+
+    #[test]
+    fn test_estimate_synthetic_bags() {
+        for (spec, handful, seed) in [
+            ("5 red, 3 green, 2 blue", 6, 1),
+            ("8 red, 4 green, 6 blue", 12, 7),
+            ("4 red, 9 purple", 10, 2023),
+        ] {
+            let bag = Bag::from_str(spec).unwrap();
+            let draws = synthetic_draws(&bag, handful, 400, seed);
+            let draws: Vec<&Draw> = draws.iter().collect();
+            let estimate = estimate_bag(&draws, 60).unwrap();
+            for colour in bag.colours() {
+                let interval = estimate.intervals[colour];
+                let truth = bag.count(colour);
+                assert!(
+                    interval.low <= truth && truth <= interval.high,
+                    "{spec}: {colour}"
+                );
+                assert!(interval.low <= estimate.bag.count(colour));
+                assert!(estimate.bag.count(colour) <= interval.high);
+                assert!(
+                    estimate.bag.count(colour).abs_diff(truth) <= 1,
+                    "{spec}: {colour}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_estimate_games() {
+        use crate::task1::parse_games;
+        let games = parse_games(
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+        Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        )
+        .unwrap();
+        let estimate = estimate_games(&games, 100).unwrap();
+        assert!(estimate.bag.count("red") >= 4);
+        assert!(estimate.bag.count("green") >= 3);
+        assert!(estimate.bag.count("blue") >= 6);
+        for (colour, interval) in &estimate.intervals {
+            assert!(interval.low <= estimate.bag.count(colour));
+            assert!(estimate.bag.count(colour) <= interval.high);
+        }
     }
 }
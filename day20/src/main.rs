@@ -0,0 +1,350 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+    println!("Task2 answer: {}", task2::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    use std::collections::{HashMap, VecDeque};
+
+    pub fn handle_input(input: &str) -> usize {
+        let mut network = Network::from_str(input);
+        let mut low = 0;
+        let mut high = 0;
+        for _ in 0..1000 {
+            network.press(|signal| match signal.pulse {
+                Pulse::Low => low += 1,
+                Pulse::High => high += 1,
+            });
+        }
+        low * high
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a"
+            ),
+            32000000
+        );
+        assert_eq!(
+            handle_input(
+                "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output"
+            ),
+            11687500
+        );
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Pulse {
+        Low,
+        High,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Signal {
+        pub from: String,
+        pub to: String,
+        pub pulse: Pulse,
+    }
+
+    impl Signal {
+        fn new(from: &str, to: &str, pulse: Pulse) -> Self {
+            Self {
+                from: from.to_string(),
+                to: to.to_string(),
+                pulse,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Kind {
+        Broadcaster,
+        FlipFlop(bool),
+        Conjunction(HashMap<String, Pulse>),
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Module {
+        kind: Kind,
+        pub outputs: Vec<String>,
+    }
+
+    impl Module {
+        fn from_str(input: &str) -> (String, Self) {
+            let (name, outputs) = input.trim().split_once(" -> ").unwrap();
+            let outputs = outputs.split(", ").map(|o| o.to_string()).collect();
+            let (name, kind) = match name.chars().next().unwrap() {
+                '%' => (&name[1..], Kind::FlipFlop(false)),
+                '&' => (&name[1..], Kind::Conjunction(HashMap::new())),
+                _ => (name, Kind::Broadcaster),
+            };
+            (name.to_string(), Self { kind, outputs })
+        }
+
+        fn receive(&mut self, signal: &Signal) -> Option<Pulse> {
+            match &mut self.kind {
+                Kind::Broadcaster => Some(signal.pulse),
+                Kind::FlipFlop(on) => match signal.pulse {
+                    Pulse::High => None,
+                    Pulse::Low => {
+                        *on = !*on;
+                        Some(if *on { Pulse::High } else { Pulse::Low })
+                    }
+                },
+                Kind::Conjunction(memory) => {
+                    memory.insert(signal.from.clone(), signal.pulse);
+                    if memory.values().all(|p| *p == Pulse::High) {
+                        Some(Pulse::Low)
+                    } else {
+                        Some(Pulse::High)
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_module() {
+        assert_eq!(
+            Module::from_str("broadcaster -> a, b, c"),
+            (
+                "broadcaster".to_string(),
+                Module {
+                    kind: Kind::Broadcaster,
+                    outputs: vec!["a".to_string(), "b".to_string(), "c".to_string()]
+                }
+            )
+        );
+        assert_eq!(
+            Module::from_str("%a -> b"),
+            (
+                "a".to_string(),
+                Module {
+                    kind: Kind::FlipFlop(false),
+                    outputs: vec!["b".to_string()]
+                }
+            )
+        );
+        assert_eq!(
+            Module::from_str("&inv -> a"),
+            (
+                "inv".to_string(),
+                Module {
+                    kind: Kind::Conjunction(HashMap::new()),
+                    outputs: vec!["a".to_string()]
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_receive() {
+        let (_, mut flip_flop) = Module::from_str("%a -> b");
+        assert_eq!(flip_flop.receive(&Signal::new("x", "a", Pulse::High)), None);
+        assert_eq!(
+            flip_flop.receive(&Signal::new("x", "a", Pulse::Low)),
+            Some(Pulse::High)
+        );
+        assert_eq!(
+            flip_flop.receive(&Signal::new("x", "a", Pulse::Low)),
+            Some(Pulse::Low)
+        );
+
+        let (_, mut conjunction) = Module::from_str("&inv -> a");
+        conjunction.kind = Kind::Conjunction(HashMap::from([
+            ("x".to_string(), Pulse::Low),
+            ("y".to_string(), Pulse::Low),
+        ]));
+        assert_eq!(
+            conjunction.receive(&Signal::new("x", "inv", Pulse::High)),
+            Some(Pulse::High)
+        );
+        assert_eq!(
+            conjunction.receive(&Signal::new("y", "inv", Pulse::High)),
+            Some(Pulse::Low)
+        );
+    }
+
+    pub struct Network {
+        pub modules: HashMap<String, Module>,
+    }
+
+    impl Network {
+        pub fn from_str(input: &str) -> Self {
+            let mut modules: HashMap<String, Module> =
+                input.lines().map(Module::from_str).collect();
+
+            let mut connections = vec![];
+            for (name, m) in &modules {
+                for o in &m.outputs {
+                    connections.push((name.clone(), o.clone()));
+                }
+            }
+            for (from, to) in connections {
+                if let Some(Module {
+                    kind: Kind::Conjunction(memory),
+                    ..
+                }) = modules.get_mut(&to)
+                {
+                    memory.insert(from, Pulse::Low);
+                }
+            }
+
+            Self { modules }
+        }
+
+        pub fn inputs(&self, name: &str) -> Vec<&String> {
+            self.modules
+                .iter()
+                .filter(|(_, m)| m.outputs.iter().any(|o| o == name))
+                .map(|(n, _)| n)
+                .collect()
+        }
+
+        pub fn press<F>(&mut self, mut on_signal: F)
+        where
+            F: FnMut(&Signal),
+        {
+            let mut queue = VecDeque::from([Signal::new("button", "broadcaster", Pulse::Low)]);
+            while let Some(signal) = queue.pop_front() {
+                on_signal(&signal);
+                let Some(module) = self.modules.get_mut(&signal.to) else {
+                    continue;
+                };
+                if let Some(pulse) = module.receive(&signal) {
+                    for o in &module.outputs {
+                        queue.push_back(Signal::new(&signal.to, o, pulse));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_press() {
+        let mut network = Network::from_str(
+            "broadcaster -> a, b, c
+%a -> b
+%b -> c
+%c -> inv
+&inv -> a",
+        );
+        let mut signals = vec![];
+        network.press(|s| signals.push((s.from.clone(), s.to.clone(), s.pulse)));
+        assert_eq!(signals.len(), 12);
+        assert_eq!(
+            signals[0],
+            ("button".to_string(), "broadcaster".to_string(), Pulse::Low)
+        );
+        assert_eq!(
+            signals[11],
+            ("inv".to_string(), "a".to_string(), Pulse::High)
+        );
+    }
+
+    #[test]
+    fn test_inputs() {
+        let network = Network::from_str(
+            "broadcaster -> a
+%a -> inv, con
+&inv -> b
+%b -> con
+&con -> output",
+        );
+        let mut inputs = network.inputs("con");
+        inputs.sort();
+        assert_eq!(inputs, vec!["a", "b"]);
+    }
+}
+
+mod task2 {
+    use crate::task1::{Network, Pulse};
+    use std::collections::HashMap;
+
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    fn lcm<I>(nums: I) -> usize
+    where
+        I: Iterator<Item = usize>,
+    {
+        nums.fold(1, |num, ans| num * ans / gcd(num, ans))
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(lcm([2, 3, 4].into_iter()), 12);
+        assert_eq!(lcm([3739, 3761, 3797, 3889].into_iter()), 207652583562007);
+    }
+
+    pub fn handle_input(input: &str) -> usize {
+        presses_until_low(input, "rx")
+    }
+
+    fn presses_until_low(input: &str, target: &str) -> usize {
+        let mut network = Network::from_str(input);
+        let hub = network.inputs(target)[0].clone();
+        let mut cycles: HashMap<String, usize> = network
+            .inputs(&hub)
+            .into_iter()
+            .map(|i| (i.clone(), 0))
+            .collect();
+
+        for press in 1.. {
+            network.press(|signal| {
+                if signal.to == hub && signal.pulse == Pulse::High && cycles[&signal.from] == 0 {
+                    cycles.insert(signal.from.clone(), press);
+                }
+            });
+            if cycles.values().all(|c| *c != 0) {
+                break;
+            }
+        }
+        lcm(cycles.into_values())
+    }
+
+    #[test]
+    fn test_presses_until_low() {
+        let input = "broadcaster -> a, b
+%a -> x
+&x -> hub
+%b -> c
+%c -> y
+&y -> hub
+&hub -> rx";
+        assert_eq!(presses_until_low(input, "rx"), 4);
+
+        let mut network = Network::from_str(input);
+        let mut presses = 0;
+        let mut delivered = false;
+        while !delivered {
+            presses += 1;
+            network.press(|s| delivered |= s.to == "rx" && s.pulse == Pulse::Low);
+        }
+        assert_eq!(presses, 4);
+    }
+}
@@ -0,0 +1,227 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+    println!("Task2 answer: {}", task2::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    use std::collections::{HashSet, VecDeque};
+
+    pub fn handle_input(input: &str) -> usize {
+        Garden::from_str(input).reachable(64, false)
+    }
+
+    #[test]
+    fn test_reachable() {
+        let garden = Garden::from_str(
+            "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........",
+        );
+        assert_eq!(garden.reachable(1, false), 2);
+        assert_eq!(garden.reachable(2, false), 4);
+        assert_eq!(garden.reachable(3, false), 6);
+        assert_eq!(garden.reachable(6, false), 16);
+    }
+
+    #[test]
+    fn test_reachable_infinite() {
+        let garden = Garden::from_str(
+            "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........",
+        );
+        assert_eq!(garden.reachable(6, true), 16);
+        assert_eq!(garden.reachable(10, true), 50);
+        assert_eq!(garden.reachable(50, true), 1594);
+        assert_eq!(garden.reachable(100, true), 6536);
+        assert_eq!(garden.reachable(500, true), 167004);
+    }
+
+    pub struct Garden {
+        rocks: Vec<Vec<bool>>,
+        start: (i64, i64),
+        pub width: i64,
+        pub height: i64,
+    }
+
+    impl Garden {
+        pub fn from_str(input: &str) -> Self {
+            let mut start = (0, 0);
+            let rocks: Vec<Vec<bool>> = input
+                .lines()
+                .enumerate()
+                .map(|(y, l)| {
+                    l.trim()
+                        .chars()
+                        .enumerate()
+                        .map(|(x, c)| {
+                            if c == 'S' {
+                                start = (x as i64, y as i64);
+                            }
+                            c == '#'
+                        })
+                        .collect()
+                })
+                .collect();
+            Self {
+                start,
+                width: rocks[0].len() as i64,
+                height: rocks.len() as i64,
+                rocks,
+            }
+        }
+
+        fn is_rock(&self, x: i64, y: i64) -> bool {
+            self.rocks[y.rem_euclid(self.height) as usize][x.rem_euclid(self.width) as usize]
+        }
+
+        fn in_bounds(&self, x: i64, y: i64) -> bool {
+            x >= 0 && y >= 0 && x < self.width && y < self.height
+        }
+
+        pub fn reachable(&self, steps: usize, infinite: bool) -> usize {
+            let mut visited = HashSet::from([self.start]);
+            let mut queue = VecDeque::from([(self.start, 0)]);
+            let mut count = 0;
+            while let Some(((x, y), distance)) = queue.pop_front() {
+                if distance % 2 == steps % 2 {
+                    count += 1;
+                }
+                if distance == steps {
+                    continue;
+                }
+                for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                    if (!infinite && !self.in_bounds(nx, ny))
+                        || self.is_rock(nx, ny)
+                        || !visited.insert((nx, ny))
+                    {
+                        continue;
+                    }
+                    queue.push_back(((nx, ny), distance + 1));
+                }
+            }
+            count
+        }
+    }
+}
+
+mod task2 {
+    use crate::task1::Garden;
+
+    pub fn handle_input(input: &str) -> usize {
+        extrapolate(&Garden::from_str(input), 26501365, 0)
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let garden = Garden::from_str(
+            "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........",
+        );
+        assert_eq!(extrapolate(&garden, 500, 4), 167004);
+        assert_eq!(extrapolate(&garden, 1000, 4), 668697);
+        assert_eq!(extrapolate(&garden, 5000, 4), 16733044);
+    }
+
+    pub fn extrapolate(garden: &Garden, steps: usize, warmup: usize) -> usize {
+        let size = garden.width as usize;
+        let offset = steps % size;
+        let cycles = steps / size;
+        if cycles < warmup + 3 {
+            return garden.reachable(steps, true);
+        }
+        let samples: Vec<i64> = (warmup..warmup + 3)
+            .map(|i| garden.reachable(offset + i * size, true) as i64)
+            .collect();
+        extrapolate_sequence(&samples, cycles - warmup - 2) as usize
+    }
+
+    fn diff_sequence(sequence: &[i64]) -> Vec<i64> {
+        sequence.windows(2).map(|w| w[1] - w[0]).collect()
+    }
+
+    #[test]
+    fn test_diff_sequence() {
+        assert_eq!(diff_sequence(&[1, 4, 9, 16]), vec![3, 5, 7]);
+    }
+
+    fn produce_sequences(start: &[i64]) -> Vec<Vec<i64>> {
+        let mut sequences: Vec<Vec<i64>> = vec![];
+        let mut current = start;
+        loop {
+            let diff = diff_sequence(current);
+            if diff.iter().all(|d| *d == 0) {
+                break;
+            }
+            sequences.push(diff);
+            current = &sequences[sequences.len() - 1];
+        }
+        sequences
+    }
+
+    #[test]
+    fn test_produce_sequences() {
+        assert_eq!(
+            produce_sequences(&[1, 4, 9, 16]),
+            vec![vec![3, 5, 7], vec![2, 2]]
+        );
+    }
+
+    fn extrapolate_sequence(start: &[i64], count: usize) -> i64 {
+        let mut lasts: Vec<i64> = produce_sequences(start)
+            .iter()
+            .map(|s| s[s.len() - 1])
+            .collect();
+        let mut value = start[start.len() - 1];
+        for _ in 0..count {
+            for i in (1..lasts.len()).rev() {
+                lasts[i - 1] += lasts[i];
+            }
+            if let Some(diff) = lasts.first() {
+                value += diff;
+            }
+        }
+        value
+    }
+
+    #[test]
+    fn test_extrapolate_sequence() {
+        assert_eq!(extrapolate_sequence(&[1, 4, 9], 1), 16);
+        assert_eq!(extrapolate_sequence(&[1, 4, 9], 7), 100);
+        assert_eq!(extrapolate_sequence(&[5, 5, 5], 3), 5);
+    }
+}
@@ -0,0 +1,247 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+    println!("Task2 answer: {}", task2::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    use std::collections::HashMap;
+
+    pub fn handle_input(input: &str) -> usize {
+        let tower = Tower::new(input);
+        (0..tower.bricks.len())
+            .filter(|b| tower.can_disintegrate(*b))
+            .count()
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9"
+            ),
+            5
+        );
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Brick {
+        start: (usize, usize, usize),
+        end: (usize, usize, usize),
+    }
+
+    impl Brick {
+        fn from_str(input: &str) -> Self {
+            let (start, end) = input.trim().split_once("~").unwrap();
+            Self {
+                start: parse_point(start),
+                end: parse_point(end),
+            }
+        }
+
+        fn footprint(&self) -> Vec<(usize, usize)> {
+            let mut results = vec![];
+            for x in self.start.0..=self.end.0 {
+                for y in self.start.1..=self.end.1 {
+                    results.push((x, y));
+                }
+            }
+            results
+        }
+
+        fn height(&self) -> usize {
+            self.end.2 - self.start.2 + 1
+        }
+    }
+
+    fn parse_point(input: &str) -> (usize, usize, usize) {
+        let coords: Vec<usize> = input
+            .split(",")
+            .map(|c| c.parse::<usize>().unwrap())
+            .collect();
+        (coords[0], coords[1], coords[2])
+    }
+
+    #[test]
+    fn test_parse_brick() {
+        assert_eq!(
+            Brick::from_str("1,0,1~1,2,1"),
+            Brick {
+                start: (1, 0, 1),
+                end: (1, 2, 1)
+            }
+        );
+    }
+
+    #[test]
+    fn test_footprint() {
+        assert_eq!(
+            Brick::from_str("1,0,1~1,2,1").footprint(),
+            vec![(1, 0), (1, 1), (1, 2)]
+        );
+        assert_eq!(Brick::from_str("1,1,8~1,1,9").footprint(), vec![(1, 1)]);
+        assert_eq!(Brick::from_str("1,1,8~1,1,9").height(), 2);
+    }
+
+    pub struct Tower {
+        pub bricks: Vec<Brick>,
+        pub supports: Vec<Vec<usize>>,
+        pub supported_by: Vec<Vec<usize>>,
+    }
+
+    impl Tower {
+        pub fn new(input: &str) -> Self {
+            let mut bricks: Vec<Brick> = input.lines().map(Brick::from_str).collect();
+            bricks.sort_by_key(|b| b.start.2);
+
+            let mut supports = vec![vec![]; bricks.len()];
+            let mut supported_by = vec![vec![]; bricks.len()];
+            let mut height_map: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+
+            for (i, brick) in bricks.iter_mut().enumerate() {
+                let footprint = brick.footprint();
+                let top = footprint
+                    .iter()
+                    .filter_map(|p| height_map.get(p))
+                    .map(|(z, _)| *z)
+                    .max()
+                    .unwrap_or(0);
+
+                for p in &footprint {
+                    if let Some((z, below)) = height_map.get(p) {
+                        if *z == top && !supported_by[i].contains(below) {
+                            supported_by[i].push(*below);
+                            supports[*below].push(i);
+                        }
+                    }
+                }
+
+                let height = brick.height();
+                brick.start.2 = top + 1;
+                brick.end.2 = top + height;
+                for p in footprint {
+                    height_map.insert(p, (brick.end.2, i));
+                }
+            }
+
+            Self {
+                bricks,
+                supports,
+                supported_by,
+            }
+        }
+
+        fn can_disintegrate(&self, brick: usize) -> bool {
+            self.supports[brick]
+                .iter()
+                .all(|above| self.supported_by[*above].len() > 1)
+        }
+    }
+
+    #[test]
+    fn test_tower() {
+        let tower = Tower::new(
+            "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9",
+        );
+        assert_eq!(tower.supports[0], vec![1, 2]);
+        assert_eq!(tower.supported_by[3], vec![1, 2]);
+        assert_eq!(tower.supported_by[6], vec![5]);
+        assert_eq!(
+            tower.bricks[6],
+            Brick {
+                start: (1, 1, 5),
+                end: (1, 1, 6)
+            }
+        );
+        assert!(!tower.can_disintegrate(0));
+        assert!(tower.can_disintegrate(1));
+        assert!(!tower.can_disintegrate(5));
+        assert!(tower.can_disintegrate(6));
+    }
+}
+
+mod task2 {
+    use crate::task1::Tower;
+    use std::collections::VecDeque;
+
+    pub fn handle_input(input: &str) -> usize {
+        let tower = Tower::new(input);
+        (0..tower.bricks.len())
+            .map(|b| tower.count_falling(b))
+            .sum()
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9"
+            ),
+            7
+        );
+    }
+
+    impl Tower {
+        fn count_falling(&self, brick: usize) -> usize {
+            let mut fallen = vec![false; self.bricks.len()];
+            fallen[brick] = true;
+            let mut queue = VecDeque::from([brick]);
+            let mut count = 0;
+            while let Some(current) = queue.pop_front() {
+                for above in &self.supports[current] {
+                    if fallen[*above] {
+                        continue;
+                    }
+                    if self.supported_by[*above].iter().all(|b| fallen[*b]) {
+                        fallen[*above] = true;
+                        count += 1;
+                        queue.push_back(*above);
+                    }
+                }
+            }
+            count
+        }
+    }
+
+    #[test]
+    fn test_count_falling() {
+        let tower = Tower::new(
+            "1,0,1~1,2,1
+0,0,2~2,0,2
+0,2,3~2,2,3
+0,0,4~0,2,4
+2,0,5~2,2,5
+0,1,6~2,1,6
+1,1,8~1,1,9",
+        );
+        assert_eq!(tower.count_falling(0), 6);
+        assert_eq!(tower.count_falling(5), 1);
+        assert_eq!(tower.count_falling(1), 0);
+    }
+}
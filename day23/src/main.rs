@@ -0,0 +1,265 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+    println!("Task2 answer: {}", task2::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    use std::collections::HashMap;
+
+    pub fn handle_input(input: &str) -> usize {
+        Graph::new(input, true).longest_path()
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#"
+            ),
+            94
+        );
+    }
+
+    type Point = (usize, usize);
+
+    const DIRECTIONS: [(isize, isize, char); 4] =
+        [(0, -1, '^'), (1, 0, '>'), (0, 1, 'v'), (-1, 0, '<')];
+
+    struct Map {
+        tiles: Vec<Vec<char>>,
+        slippery: bool,
+    }
+
+    impl Map {
+        fn new(input: &str, slippery: bool) -> Self {
+            Self {
+                tiles: input.lines().map(|l| l.trim().chars().collect()).collect(),
+                slippery,
+            }
+        }
+
+        fn start(&self) -> Point {
+            (self.tiles[0].iter().position(|t| *t == '.').unwrap(), 0)
+        }
+
+        fn end(&self) -> Point {
+            let y = self.tiles.len() - 1;
+            (self.tiles[y].iter().position(|t| *t == '.').unwrap(), y)
+        }
+
+        fn is_open(&self, (x, y): Point) -> bool {
+            self.tiles[y][x] != '#'
+        }
+
+        fn adjacent(&self, (x, y): Point) -> Vec<(Point, char)> {
+            let mut results = vec![];
+            for (dx, dy, slope) in DIRECTIONS {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if ny < self.tiles.len() && nx < self.tiles[ny].len() && self.is_open((nx, ny)) {
+                    results.push(((nx, ny), slope));
+                }
+            }
+            results
+        }
+
+        fn neighbours(&self, (x, y): Point) -> Vec<Point> {
+            let tile = self.tiles[y][x];
+            self.adjacent((x, y))
+                .into_iter()
+                .filter(|(_, slope)| !self.slippery || tile == '.' || tile == *slope)
+                .map(|(p, _)| p)
+                .collect()
+        }
+
+        fn is_junction(&self, point: Point) -> bool {
+            self.adjacent(point).len() > 2
+        }
+
+        fn junctions(&self) -> Vec<Point> {
+            let mut results = vec![self.start(), self.end()];
+            for y in 0..self.tiles.len() {
+                for x in 0..self.tiles[y].len() {
+                    if self.is_open((x, y)) && self.is_junction((x, y)) {
+                        results.push((x, y));
+                    }
+                }
+            }
+            results
+        }
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let map = Map::new(
+            "#.###
+#.>.#
+###.#",
+            true,
+        );
+        assert_eq!(map.start(), (1, 0));
+        assert_eq!(map.end(), (3, 2));
+        assert_eq!(map.neighbours((1, 1)), vec![(1, 0), (2, 1)]);
+        assert_eq!(map.neighbours((2, 1)), vec![(3, 1)]);
+
+        let map = Map::new(
+            "#.###
+#.>.#
+###.#",
+            false,
+        );
+        assert_eq!(map.neighbours((2, 1)), vec![(3, 1), (1, 1)]);
+    }
+
+    pub struct Graph {
+        edges: Vec<Vec<(usize, usize)>>,
+        start: usize,
+        end: usize,
+    }
+
+    impl Graph {
+        pub fn new(input: &str, slippery: bool) -> Self {
+            let map = Map::new(input, slippery);
+            let junctions = map.junctions();
+            assert!(junctions.len() <= 64);
+            let ids: HashMap<Point, usize> =
+                junctions.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+
+            let mut edges = vec![vec![]; junctions.len()];
+            for (from, junction) in junctions.iter().enumerate() {
+                for first in map.neighbours(*junction) {
+                    let mut prev = *junction;
+                    let mut current = first;
+                    let mut length = 1;
+                    loop {
+                        if let Some(to) = ids.get(&current) {
+                            edges[from].push((*to, length));
+                            break;
+                        }
+                        let Some(next) = map.neighbours(current).into_iter().find(|n| *n != prev)
+                        else {
+                            break;
+                        };
+                        prev = current;
+                        current = next;
+                        length += 1;
+                    }
+                }
+            }
+
+            Self {
+                edges,
+                start: ids[&map.start()],
+                end: ids[&map.end()],
+            }
+        }
+
+        pub fn longest_path(&self) -> usize {
+            self.longest_path_from(self.start, 1 << self.start).unwrap()
+        }
+
+        fn longest_path_from(&self, current: usize, visited: u64) -> Option<usize> {
+            if current == self.end {
+                return Some(0);
+            }
+            let mut longest = None;
+            for (next, length) in &self.edges[current] {
+                if visited & (1 << next) != 0 {
+                    continue;
+                }
+                if let Some(rest) = self.longest_path_from(*next, visited | (1 << next)) {
+                    longest = longest.max(Some(length + rest));
+                }
+            }
+            longest
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = Graph::new(
+            "#.#####
+#.....#
+#.###.#
+#.....#
+#####.#",
+            false,
+        );
+        assert_eq!(graph.edges.len(), 4);
+        assert_eq!(graph.edges[graph.start], vec![(2, 1)]);
+        assert_eq!(graph.longest_path(), 8);
+    }
+}
+
+mod task2 {
+    use crate::task1::Graph;
+
+    pub fn handle_input(input: &str) -> usize {
+        Graph::new(input, false).longest_path()
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "#.#####################
+#.......#########...###
+#######.#########.#.###
+###.....#.>.>.###.#.###
+###v#####.#v#.###.#.###
+###.>...#.#.#.....#...#
+###v###.#.#.#########.#
+###...#.#.#.......#...#
+#####.#.#.#######.#.###
+#.....#.#.#.......#...#
+#.#####.#.#.#########v#
+#.#...#...#...###...>.#
+#.#.#v#######v###.###v#
+#...#.>.#...>.>.#.###.#
+#####v#.#.###v#.#.###.#
+#.....#...#...#.#.#...#
+#.#########.###.#.#.###
+#...###...#...#...#.###
+###.###.#.###v#####v###
+#...#...#.#.>.>.#.>.###
+#.###.###.#.###.#.#v###
+#.....###...###...#...#
+#####################.#"
+            ),
+            154
+        );
+    }
+}
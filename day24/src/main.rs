@@ -0,0 +1,282 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+    println!("Task2 answer: {}", task2::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    pub fn handle_input(input: &str) -> usize {
+        count_intersections(input, 200000000000000, 400000000000000)
+    }
+
+    fn count_intersections(input: &str, min: i128, max: i128) -> usize {
+        let hailstones = parse_hailstones(input);
+        let mut count = 0;
+        for i in 0..hailstones.len() {
+            for j in i + 1..hailstones.len() {
+                if hailstones[i].intersects_within(&hailstones[j], min, max) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn test_count_intersections() {
+        assert_eq!(
+            count_intersections(
+                "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3",
+                7,
+                27
+            ),
+            2
+        );
+    }
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Vector {
+        pub x: i128,
+        pub y: i128,
+        pub z: i128,
+    }
+
+    impl Vector {
+        pub fn new(x: i128, y: i128, z: i128) -> Self {
+            Self { x, y, z }
+        }
+
+        fn from_str(input: &str) -> Self {
+            let coords: Vec<i128> = input
+                .split(",")
+                .map(|c| c.trim().parse::<i128>().unwrap())
+                .collect();
+            Self::new(coords[0], coords[1], coords[2])
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Hailstone {
+        pub position: Vector,
+        pub velocity: Vector,
+    }
+
+    impl Hailstone {
+        fn from_str(input: &str) -> Self {
+            let (position, velocity) = input.split_once("@").unwrap();
+            Self {
+                position: Vector::from_str(position),
+                velocity: Vector::from_str(velocity),
+            }
+        }
+
+        fn intersects_within(&self, other: &Hailstone, min: i128, max: i128) -> bool {
+            let (p, v) = (self.position, self.velocity);
+            let (q, w) = (other.position, other.velocity);
+
+            let mut det = v.x * w.y - v.y * w.x;
+            if det == 0 {
+                return false;
+            }
+            let (dx, dy) = (q.x - p.x, q.y - p.y);
+            let mut t = dx * w.y - dy * w.x;
+            let mut s = dx * v.y - dy * v.x;
+            if det < 0 {
+                det = -det;
+                t = -t;
+                s = -s;
+            }
+            if t < 0 || s < 0 {
+                return false;
+            }
+
+            let x = p.x * det + t * v.x;
+            let y = p.y * det + t * v.y;
+            min * det <= x && x <= max * det && min * det <= y && y <= max * det
+        }
+    }
+
+    #[test]
+    fn test_intersects_within() {
+        let a = Hailstone::from_str("19, 13, 30 @ -2, 1, -2");
+        assert!(a.intersects_within(&Hailstone::from_str("18, 19, 22 @ -1, -1, -2"), 7, 27));
+        assert!(a.intersects_within(&Hailstone::from_str("20, 25, 34 @ -2, -2, -4"), 7, 27));
+        assert!(!a.intersects_within(&Hailstone::from_str("12, 31, 28 @ -1, -2, -1"), 7, 27));
+        assert!(!a.intersects_within(&Hailstone::from_str("20, 19, 15 @ 1, -5, -3"), 7, 27));
+
+        let b = Hailstone::from_str("18, 19, 22 @ -1, -1, -2");
+        assert!(!b.intersects_within(&Hailstone::from_str("20, 25, 34 @ -2, -2, -4"), 7, 27));
+    }
+
+    pub fn parse_hailstones(input: &str) -> Vec<Hailstone> {
+        input.lines().map(Hailstone::from_str).collect()
+    }
+
+    #[test]
+    fn test_parse_hailstones() {
+        assert_eq!(
+            parse_hailstones("19, 13, 30 @ -2,  1, -2"),
+            vec![Hailstone {
+                position: Vector::new(19, 13, 30),
+                velocity: Vector::new(-2, 1, -2)
+            }]
+        );
+    }
+}
+
+mod task2 {
+    use crate::task1::{parse_hailstones, Hailstone, Vector};
+    use std::ops::{Add, Mul, Sub};
+
+    pub fn handle_input(input: &str) -> i128 {
+        let rock = throw_rock(&parse_hailstones(input));
+        rock.position.x + rock.position.y + rock.position.z
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3"
+            ),
+            47
+        );
+    }
+
+    impl Add for Vector {
+        type Output = Vector;
+
+        fn add(self, other: Vector) -> Vector {
+            Vector::new(self.x + other.x, self.y + other.y, self.z + other.z)
+        }
+    }
+
+    impl Sub for Vector {
+        type Output = Vector;
+
+        fn sub(self, other: Vector) -> Vector {
+            Vector::new(self.x - other.x, self.y - other.y, self.z - other.z)
+        }
+    }
+
+    impl Mul<i128> for Vector {
+        type Output = Vector;
+
+        fn mul(self, scale: i128) -> Vector {
+            Vector::new(self.x * scale, self.y * scale, self.z * scale)
+        }
+    }
+
+    fn gcd(a: i128, b: i128) -> i128 {
+        if b == 0 {
+            a.abs()
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    impl Vector {
+        fn cross(&self, other: &Vector) -> Vector {
+            Vector::new(
+                self.y * other.z - self.z * other.y,
+                self.z * other.x - self.x * other.z,
+                self.x * other.y - self.y * other.x,
+            )
+        }
+
+        fn reduce(&self) -> Vector {
+            let divisor = gcd(gcd(self.x, self.y), self.z);
+            Vector::new(self.x / divisor, self.y / divisor, self.z / divisor)
+        }
+
+        fn divide(&self, divisor: i128) -> Vector {
+            assert!(self.x % divisor == 0 && self.y % divisor == 0 && self.z % divisor == 0);
+            Vector::new(self.x / divisor, self.y / divisor, self.z / divisor)
+        }
+    }
+
+    #[test]
+    fn test_vector() {
+        let a = Vector::new(1, 0, 0);
+        let b = Vector::new(0, 1, 0);
+        assert_eq!(a.cross(&b), Vector::new(0, 0, 1));
+        assert_eq!(Vector::new(6, -9, 12).reduce(), Vector::new(2, -3, 4));
+        assert_eq!(Vector::new(6, -9, 12).divide(3), Vector::new(2, -3, 4));
+        assert_eq!(a + b * 2 - a, Vector::new(0, 2, 0));
+    }
+
+    fn collision_time(hailstone: &Hailstone, direction: &Vector) -> i128 {
+        let numerator = hailstone.position.cross(direction);
+        let denominator = hailstone.velocity.cross(direction);
+        let (numerator, denominator) = if denominator.x != 0 {
+            (numerator.x, denominator.x)
+        } else if denominator.y != 0 {
+            (numerator.y, denominator.y)
+        } else {
+            (numerator.z, denominator.z)
+        };
+        assert!(numerator % denominator == 0);
+        -numerator / denominator
+    }
+
+    fn throw_rock(hailstones: &[Hailstone]) -> Hailstone {
+        let origin = &hailstones[0];
+        let relative: Vec<Hailstone> = hailstones[1..3]
+            .iter()
+            .map(|h| Hailstone {
+                position: h.position - origin.position,
+                velocity: h.velocity - origin.velocity,
+            })
+            .collect();
+
+        let first_plane = relative[0].position.cross(&relative[0].velocity);
+        let second_plane = relative[1].position.cross(&relative[1].velocity);
+        let direction = first_plane.cross(&second_plane).reduce();
+
+        let t1 = collision_time(&relative[0], &direction);
+        let t2 = collision_time(&relative[1], &direction);
+        let c1 = relative[0].position + relative[0].velocity * t1;
+        let c2 = relative[1].position + relative[1].velocity * t2;
+
+        let velocity = (c2 - c1).divide(t2 - t1);
+        let position = c1 - velocity * t1;
+        Hailstone {
+            position: position + origin.position,
+            velocity: velocity + origin.velocity,
+        }
+    }
+
+    #[test]
+    fn test_throw_rock() {
+        let hailstones = parse_hailstones(
+            "19, 13, 30 @ -2,  1, -2
+18, 19, 22 @ -1, -1, -2
+20, 25, 34 @ -2, -2, -4
+12, 31, 28 @ -1, -2, -1
+20, 19, 15 @  1, -5, -3",
+        );
+        assert_eq!(
+            throw_rock(&hailstones),
+            Hailstone {
+                position: Vector::new(24, 13, 10),
+                velocity: Vector::new(-3, 1, 2)
+            }
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+fn main() -> std::io::Result<()> {
+    let mut file = File::open("input.txt")?;
+    let mut input = String::new();
+    file.read_to_string(&mut input)?;
+
+    println!("Task1 answer: {}", task1::handle_input(&input));
+
+    Ok(())
+}
+
+mod task1 {
+    use std::collections::HashMap;
+
+    pub fn handle_input(input: &str) -> usize {
+        let (a, b) = Graph::from_str(input).three_cut(2023);
+        a * b
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(
+                "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr"
+            ),
+            54
+        );
+    }
+
+    struct Random {
+        state: u64,
+    }
+
+    impl Random {
+        fn new(seed: u64) -> Self {
+            Self { state: seed.max(1) }
+        }
+
+        fn next(&mut self) -> u64 {
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            self.state
+        }
+
+        fn shuffle<T>(&mut self, items: &mut [T]) {
+            for i in (1..items.len()).rev() {
+                let j = (self.next() % (i as u64 + 1)) as usize;
+                items.swap(i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+        assert_eq!(a.next(), b.next());
+        assert_ne!(a.next(), Random::new(43).next());
+
+        let mut items = vec![1, 2, 3, 4, 5];
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, vec![1, 2, 3, 4, 5]);
+    }
+
+    struct DisjointSet {
+        parents: Vec<usize>,
+        sizes: Vec<usize>,
+    }
+
+    impl DisjointSet {
+        fn new(size: usize) -> Self {
+            Self {
+                parents: (0..size).collect(),
+                sizes: vec![1; size],
+            }
+        }
+
+        fn find(&mut self, node: usize) -> usize {
+            let parent = self.parents[node];
+            if parent == node {
+                return node;
+            }
+            let root = self.find(parent);
+            self.parents[node] = root;
+            root
+        }
+
+        fn union(&mut self, a: usize, b: usize) -> bool {
+            let (a, b) = (self.find(a), self.find(b));
+            if a == b {
+                return false;
+            }
+            let (big, small) = if self.sizes[a] < self.sizes[b] {
+                (b, a)
+            } else {
+                (a, b)
+            };
+            self.parents[small] = big;
+            self.sizes[big] += self.sizes[small];
+            true
+        }
+    }
+
+    #[test]
+    fn test_disjoint_set() {
+        let mut set = DisjointSet::new(4);
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 3));
+        assert!(!set.union(1, 0));
+        assert_ne!(set.find(0), set.find(2));
+        assert!(set.union(1, 3));
+        assert_eq!(set.find(0), set.find(2));
+        let root = set.find(0);
+        assert_eq!(set.sizes[root], 4);
+    }
+
+    struct Graph {
+        nodes: usize,
+        edges: Vec<(usize, usize)>,
+    }
+
+    impl Graph {
+        fn from_str(input: &str) -> Self {
+            let mut ids: HashMap<&str, usize> = HashMap::new();
+            let mut edges = vec![];
+            for l in input.lines() {
+                let (from, to) = l.trim().split_once(":").unwrap();
+                let next = ids.len();
+                let from = *ids.entry(from).or_insert(next);
+                for t in to.split_whitespace() {
+                    let next = ids.len();
+                    edges.push((from, *ids.entry(t).or_insert(next)));
+                }
+            }
+            Self {
+                nodes: ids.len(),
+                edges,
+            }
+        }
+
+        fn contract(&self, random: &mut Random) -> (usize, usize, usize) {
+            let mut edges = self.edges.clone();
+            random.shuffle(&mut edges);
+
+            let mut set = DisjointSet::new(self.nodes);
+            let mut components = self.nodes;
+            for (a, b) in edges {
+                if components == 2 {
+                    break;
+                }
+                if set.union(a, b) {
+                    components -= 1;
+                }
+            }
+
+            let cut = self
+                .edges
+                .iter()
+                .filter(|(a, b)| set.find(*a) != set.find(*b))
+                .count();
+            let root = set.find(0);
+            let size = set.sizes[root];
+            (cut, size, self.nodes - size)
+        }
+
+        fn three_cut(&self, seed: u64) -> (usize, usize) {
+            let mut random = Random::new(seed);
+            loop {
+                let (cut, a, b) = self.contract(&mut random);
+                if cut == 3 {
+                    return (a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_graph() {
+        let graph = Graph::from_str(
+            "jqt: rhn xhk nvd
+xhk: hfx",
+        );
+        assert_eq!(graph.nodes, 5);
+        assert_eq!(graph.edges, vec![(0, 1), (0, 2), (0, 3), (2, 4)]);
+    }
+
+    #[test]
+    fn test_three_cut() {
+        let graph = Graph::from_str(
+            "jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr",
+        );
+        for seed in [1, 7, 2023] {
+            let (a, b) = graph.three_cut(seed);
+            assert_eq!(a * b, 54);
+            assert_eq!(a + b, 15);
+        }
+    }
+}
@@ -6,61 +6,105 @@ fn main() -> std::io::Result<()> {
     let mut input = String::new();
     file.read_to_string(&mut input)?;
 
-    let t1_sum = task1::handle_input(&input);
+    let schematic = task1::Schematic::new(&input);
+    let overflow =
+        || std::io::Error::new(std::io::ErrorKind::InvalidData, "gear value overflows u64");
+    let rule = match std::env::args().find_map(|a| a.strip_prefix("--gear=").map(String::from)) {
+        Some(r) => task1::GearRule::from_str(&r).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid gear rule {r:?}, expected SYMBOLS:COUNT:product|sum|max \
+                     where SYMBOLS is a set of characters or the reserved word \"any\" \
+                     and COUNT is N, LOW-HIGH with LOW <= HIGH, or LOW-"
+                ),
+            )
+        })?,
+        None => task1::GearRule::gears(),
+    };
+
+    if std::env::args().any(|a| a == "--render") {
+        print!("{}", render::ansi(&schematic, &rule).ok_or_else(overflow)?);
+        return Ok(());
+    }
+    if let Some(path) = std::env::args().find_map(|a| a.strip_prefix("--html=").map(String::from)) {
+        File::create(&path)?.write_all(
+            render::html(&schematic, &rule)
+                .ok_or_else(overflow)?
+                .as_bytes(),
+        )?;
+        println!("Wrote {path}");
+        return Ok(());
+    }
+
+    let t1_sum = task1::handle_input(&schematic);
     println!("Task1 answer: {t1_sum}");
 
-    let t2_sum = task2::handle_input(&input);
-    println!("Task1 answer: {t2_sum}");
+    let t2_sum = task2::handle_input(&schematic, &rule).ok_or_else(overflow)?;
+    println!("Task2 answer: {t2_sum}");
 
     Ok(())
 }
 
 mod task1 {
-    use regex::Match;
+    use std::collections::BTreeSet;
+
+    pub type PartId = usize;
+    pub type SymbolId = usize;
 
     #[derive(Debug, PartialEq)]
     pub struct Number {
         pub value: u32,
-        start: usize,
-        end: usize,
+        pub row: usize,
+        pub start: usize,
+        pub end: usize,
     }
 
     impl Number {
-        fn new(value: u32, start: usize, end: usize) -> Self {
-            Self { value, start, end }
-        }
-
-        fn from_match(m: Match<'_>) -> Self {
-            let mut start = m.start();
-            if start != 0 {
-                start -= 1;
-            }
+        fn new(value: u32, row: usize, start: usize, end: usize) -> Self {
             Self {
-                value: m.as_str().parse::<u32>().unwrap(),
+                value,
+                row,
                 start,
-                end: m.end(),
+                end,
             }
         }
 
         fn is_adjacent(&self, symbol: &Symbol) -> bool {
-            if (self.start <= symbol.position) && (symbol.position <= self.end) {
-                return true;
-            }
-            false
+            self.row.abs_diff(symbol.row) <= 1
+                && self.start <= symbol.position + 1
+                && symbol.position <= self.end
         }
     }
 
     #[test]
     fn test_is_adjacent() {
-        assert!(Number::new(467, 0, 3).is_adjacent(&Symbol::new(3)));
-        assert!(!Number::new(114, 4, 8).is_adjacent(&Symbol::new(3)));
+        assert!(Number::new(467, 0, 0, 3).is_adjacent(&Symbol::new('*', 1, 3)));
+        assert!(!Number::new(114, 0, 5, 8).is_adjacent(&Symbol::new('*', 1, 3)));
+        assert!(!Number::new(467, 0, 0, 3).is_adjacent(&Symbol::new('*', 2, 3)));
+        assert!(Number::new(35, 2, 2, 4).is_adjacent(&Symbol::new('*', 1, 3)));
     }
 
-    pub fn find_numbers(line: &str) -> Vec<Number> {
+    pub fn find_numbers(row: usize, line: &str) -> Vec<Number> {
         let mut results = vec![];
-        let re = regex::Regex::new(r"\d+").unwrap();
-        for number in re.find_iter(line) {
-            results.push(Number::from_match(number));
+        let chars: Vec<char> = line.chars().collect();
+        let mut column = 0;
+        while column < chars.len() {
+            if !chars[column].is_ascii_digit() {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < chars.len() && chars[column].is_ascii_digit() {
+                column += 1;
+            }
+            let digits: String = chars[start..column].iter().collect();
+            results.push(Number::new(
+                digits.parse::<u32>().unwrap(),
+                row,
+                start,
+                column,
+            ));
         }
         results
     }
@@ -68,62 +112,60 @@ mod task1 {
     #[test]
     fn test_find_numbers() {
         assert_eq!(
-            find_numbers("467..114.."),
-            vec![Number::new(467, 0, 3), Number::new(114, 4, 8)]
+            find_numbers(0, "467..114.."),
+            vec![Number::new(467, 0, 0, 3), Number::new(114, 0, 5, 8)]
+        );
+        assert_eq!(find_numbers(1, "...*......"), vec![]);
+        assert_eq!(
+            find_numbers(2, "..35..633."),
+            vec![Number::new(35, 2, 2, 4), Number::new(633, 2, 6, 9)]
+        );
+        assert_eq!(find_numbers(3, "......#..."), vec![]);
+        assert_eq!(
+            find_numbers(4, "617*......"),
+            vec![Number::new(617, 4, 0, 3)]
+        );
+        assert_eq!(
+            find_numbers(5, ".....+.58."),
+            vec![Number::new(58, 5, 7, 9)]
+        );
+        assert_eq!(
+            find_numbers(6, "..592....."),
+            vec![Number::new(592, 6, 2, 5)]
         );
-        assert_eq!(find_numbers("...*......"), vec![]);
         assert_eq!(
-            find_numbers("..35..633."),
-            vec![Number::new(35, 1, 4), Number::new(633, 5, 9)]
+            find_numbers(7, "......755."),
+            vec![Number::new(755, 7, 6, 9)]
         );
-        assert_eq!(find_numbers("......#..."), vec![]);
-        assert_eq!(find_numbers("617*......"), vec![Number::new(617, 0, 3)]);
-        assert_eq!(find_numbers(".....+.58."), vec![Number::new(58, 6, 9)]);
-        assert_eq!(find_numbers("..592....."), vec![Number::new(592, 1, 5)]);
-        assert_eq!(find_numbers("......755."), vec![Number::new(755, 5, 9)]);
-        assert_eq!(find_numbers("...$.*...."), vec![]);
+        assert_eq!(find_numbers(8, "...$.*...."), vec![]);
         assert_eq!(
-            find_numbers(".664.598.."),
-            vec![Number::new(664, 0, 4), Number::new(598, 4, 8)]
+            find_numbers(9, ".664.598.."),
+            vec![Number::new(664, 9, 1, 4), Number::new(598, 9, 5, 8)]
         );
     }
 
     #[derive(Debug, PartialEq)]
     pub struct Symbol {
-        position: usize,
+        pub symbol: char,
+        pub row: usize,
+        pub position: usize,
     }
 
     impl Symbol {
-        pub fn new(position: usize) -> Self {
-            Self { position }
-        }
-
-        pub fn find_adjacent_numbers(&self, prev: &Line, this: &Line, next: &Line) -> Vec<u32> {
-            let mut results = vec![];
-            for n in &prev.numbers {
-                if n.is_adjacent(self) {
-                    results.push(n.value);
-                }
-            }
-            for n in &this.numbers {
-                if n.is_adjacent(self) {
-                    results.push(n.value);
-                }
-            }
-            for n in &next.numbers {
-                if n.is_adjacent(self) {
-                    results.push(n.value);
-                }
+        pub fn new(symbol: char, row: usize, position: usize) -> Self {
+            Self {
+                symbol,
+                row,
+                position,
             }
-            results
         }
     }
 
-    fn find_symbols(line: &str) -> Vec<Symbol> {
+    fn find_symbols(row: usize, line: &str) -> Vec<Symbol> {
         let mut results = vec![];
         for (i, s) in line.chars().enumerate() {
-            if !s.is_ascii_digit() && !(s == '.') {
-                results.push(Symbol::new(i));
+            if !s.is_ascii_digit() && s != '.' && !s.is_whitespace() {
+                results.push(Symbol::new(s, row, i));
             }
         }
         results
@@ -131,87 +173,466 @@ mod task1 {
 
     #[test]
     fn test_find_symbols() {
-        assert_eq!(find_symbols("467..114.."), vec![]);
-        assert_eq!(find_symbols("...*......"), vec![Symbol::new(3)]);
-        assert_eq!(find_symbols("..35..633."), vec![]);
-        assert_eq!(find_symbols("......#..."), vec![Symbol::new(6)]);
-        assert_eq!(find_symbols("617*......"), vec![Symbol::new(3)]);
-        assert_eq!(find_symbols(".....+.58."), vec![Symbol::new(5)]);
-        assert_eq!(find_symbols("..592....."), vec![]);
-        assert_eq!(find_symbols("......755."), vec![]);
+        assert_eq!(find_symbols(0, "467..114.."), vec![]);
+        assert_eq!(find_symbols(1, "...*......"), vec![Symbol::new('*', 1, 3)]);
+        assert_eq!(find_symbols(2, "..35..633."), vec![]);
+        assert_eq!(find_symbols(3, "......#..."), vec![Symbol::new('#', 3, 6)]);
+        assert_eq!(find_symbols(4, "617*......"), vec![Symbol::new('*', 4, 3)]);
+        assert_eq!(find_symbols(5, ".....+.58."), vec![Symbol::new('+', 5, 5)]);
+        assert_eq!(find_symbols(6, "..592....."), vec![]);
+        assert_eq!(find_symbols(7, "......755."), vec![]);
         assert_eq!(
-            find_symbols("...$.*...."),
-            vec![Symbol::new(3), Symbol::new(5)]
+            find_symbols(8, "...$.*...."),
+            vec![Symbol::new('$', 8, 3), Symbol::new('*', 8, 5)]
         );
-        assert_eq!(find_symbols(".664.598.."), vec![]);
+        assert_eq!(find_symbols(9, ".664.598.."), vec![]);
     }
 
-    pub struct Line {
-        pub symbols: Vec<Symbol>,
+    #[test]
+    fn test_unicode_columns() {
+        assert_eq!(
+            find_numbers(0, "€42..7"),
+            vec![Number::new(42, 0, 1, 3), Number::new(7, 0, 5, 6)]
+        );
+        assert_eq!(
+            find_symbols(0, "€42→ "),
+            vec![Symbol::new('€', 0, 0), Symbol::new('→', 0, 3)]
+        );
+
+        let schematic = Schematic::new(
+            "€..5.8
+.12→..",
+        );
+        assert_eq!(
+            schematic.symbols_adjacent_to(2).collect::<Vec<_>>(),
+            vec![&Symbol::new('€', 0, 0), &Symbol::new('→', 1, 3)]
+        );
+        assert_eq!(
+            schematic
+                .parts_adjacent_to(1)
+                .map(|n| n.value)
+                .collect::<Vec<_>>(),
+            vec![5, 12]
+        );
+        assert!(!schematic.is_part(1));
+        assert_eq!(handle_input(&schematic), 17);
+    }
+
+    #[test]
+    fn test_leading_whitespace() {
+        let schematic = Schematic::new("   *\n1   ");
+        assert_eq!(schematic.symbols, vec![Symbol::new('*', 0, 3)]);
+        assert!(!schematic.is_part(0));
+        assert_eq!(handle_input(&schematic), 0);
+    }
+
+    #[test]
+    fn test_leading_unicode_whitespace() {
+        let schematic = Schematic::new(
+            "..12.
+\u{3000}*...
+\u{a0}..7#",
+        );
+        assert_eq!(
+            schematic.symbols,
+            vec![Symbol::new('*', 1, 1), Symbol::new('#', 2, 4)]
+        );
+        assert_eq!(
+            schematic
+                .parts_adjacent_to(0)
+                .map(|n| n.value)
+                .collect::<Vec<_>>(),
+            vec![12]
+        );
+        assert_eq!(
+            schematic
+                .parts_adjacent_to(1)
+                .map(|n| n.value)
+                .collect::<Vec<_>>(),
+            vec![7]
+        );
+        assert_eq!(
+            schematic.symbols_adjacent_to(0).collect::<Vec<_>>(),
+            vec![&Symbol::new('*', 1, 1)]
+        );
+        assert_eq!(
+            schematic.symbols_adjacent_to(1).collect::<Vec<_>>(),
+            vec![&Symbol::new('#', 2, 4)]
+        );
+        assert_eq!(handle_input(&schematic), 19);
+    }
+
+    pub struct Schematic {
+        pub grid: Vec<Vec<char>>,
         pub numbers: Vec<Number>,
+        pub symbols: Vec<Symbol>,
+        symbol_parts: Vec<Vec<PartId>>,
+        part_symbols: Vec<Vec<SymbolId>>,
     }
 
-    impl Line {
-        pub fn empty() -> Self {
+    impl Schematic {
+        pub fn new(input: &str) -> Self {
+            let mut grid = vec![];
+            let mut numbers = vec![];
+            let mut symbols = vec![];
+            let mut rows: Vec<Vec<PartId>> = vec![];
+            for (row, line) in input.lines().enumerate() {
+                let found = find_numbers(row, line);
+                rows.push((numbers.len()..numbers.len() + found.len()).collect());
+                numbers.extend(found);
+                symbols.extend(find_symbols(row, line));
+                grid.push(line.chars().collect());
+            }
+
+            let mut symbol_parts = vec![vec![]; symbols.len()];
+            let mut part_symbols = vec![vec![]; numbers.len()];
+            for (symbol_id, symbol) in symbols.iter().enumerate() {
+                let first = symbol.row.saturating_sub(1);
+                let last = (symbol.row + 1).min(rows.len() - 1);
+                for row in &rows[first..=last] {
+                    for part_id in row {
+                        if numbers[*part_id].is_adjacent(symbol) {
+                            symbol_parts[symbol_id].push(*part_id);
+                            part_symbols[*part_id].push(symbol_id);
+                        }
+                    }
+                }
+            }
+
             Self {
-                symbols: vec![],
-                numbers: vec![],
+                grid,
+                numbers,
+                symbols,
+                symbol_parts,
+                part_symbols,
             }
         }
 
-        pub fn new(line: &str) -> Self {
-            Self {
-                symbols: find_symbols(line),
-                numbers: find_numbers(line),
+        pub fn part_ids_adjacent_to(&self, symbol: SymbolId) -> &[PartId] {
+            &self.symbol_parts[symbol]
+        }
+
+        pub fn parts_adjacent_to(&self, symbol: SymbolId) -> impl Iterator<Item = &Number> {
+            self.part_ids_adjacent_to(symbol)
+                .iter()
+                .map(|p| &self.numbers[*p])
+        }
+
+        pub fn symbols_adjacent_to(&self, part: PartId) -> impl Iterator<Item = &Symbol> {
+            self.part_symbols[part].iter().map(|s| &self.symbols[*s])
+        }
+
+        pub fn is_part(&self, number: PartId) -> bool {
+            self.symbols_adjacent_to(number).next().is_some()
+        }
+    }
+
+    #[test]
+    fn test_schematic() {
+        let schematic = Schematic::new(
+            "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+        );
+        assert_eq!(schematic.numbers.len(), 10);
+        assert_eq!(schematic.symbols.len(), 6);
+        assert_eq!(
+            schematic
+                .parts_adjacent_to(0)
+                .map(|n| n.value)
+                .collect::<Vec<_>>(),
+            vec![467, 35]
+        );
+        assert_eq!(
+            schematic.symbols_adjacent_to(3).collect::<Vec<_>>(),
+            vec![&Symbol::new('#', 3, 6)]
+        );
+        assert_eq!(schematic.symbols_adjacent_to(1).count(), 0);
+        assert!(!schematic.is_part(1));
+        assert!(!schematic.is_part(5));
+        assert_eq!(
+            GearRule::part_numbers()
+                .parts(&schematic)
+                .into_iter()
+                .map(|p| schematic.numbers[p].value)
+                .collect::<Vec<_>>(),
+            vec![467, 35, 633, 617, 592, 755, 664, 598]
+        );
+    }
+
+    #[test]
+    fn test_shared_part() {
+        let schematic = Schematic::new(
+            "12*
+#..",
+        );
+        assert_eq!(schematic.symbols_adjacent_to(0).count(), 2);
+        assert_eq!(schematic.parts_adjacent_to(0).count(), 1);
+        assert_eq!(schematic.parts_adjacent_to(1).count(), 1);
+        assert_eq!(handle_input(&schematic), 12);
+    }
+
+    #[derive(Debug, PartialEq, Clone)]
+    pub enum Symbols {
+        Any,
+        OneOf(Vec<char>),
+    }
+
+    impl Symbols {
+        fn contains(&self, symbol: char) -> bool {
+            match self {
+                Symbols::Any => true,
+                Symbols::OneOf(symbols) => symbols.contains(&symbol),
             }
         }
+    }
 
-        fn find_adjacent_numbers(&self, prev: &Line, next: &Line) -> Vec<u32> {
-            let mut results = vec![];
-            for s in &self.symbols {
-                results.append(&mut s.find_adjacent_numbers(prev, self, next));
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Arity {
+        Exactly(usize),
+        Between(usize, usize),
+    }
+
+    impl Arity {
+        fn accepts(&self, count: usize) -> bool {
+            match self {
+                Arity::Exactly(n) => count == *n,
+                Arity::Between(low, high) => *low <= count && count <= *high,
             }
-            results
         }
     }
 
-    fn find_adjacent_numbers(input: &str) -> Vec<u32> {
-        let mut results = vec![];
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Combine {
+        Product,
+        Sum,
+        Max,
+    }
 
-        let mut this = Line::empty();
-        let mut prev = Line::empty();
-        let mut next = Line::empty();
+    impl Combine {
+        fn apply(&self, values: &[u64]) -> Option<u64> {
+            match self {
+                Combine::Product => values.iter().try_fold(1u64, |p, v| p.checked_mul(*v)),
+                Combine::Sum => values.iter().try_fold(0u64, |s, v| s.checked_add(*v)),
+                Combine::Max => Some(values.iter().copied().max().unwrap_or(0)),
+            }
+        }
+    }
 
-        for l in input.lines() {
-            prev = this;
-            this = next;
-            next = Line::new(l);
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct GearRule {
+        pub symbols: Symbols,
+        pub neighbours: Arity,
+        pub combine: Combine,
+    }
 
-            results.append(&mut this.find_adjacent_numbers(&prev, &next));
+    impl GearRule {
+        pub fn part_numbers() -> Self {
+            Self {
+                symbols: Symbols::Any,
+                neighbours: Arity::Between(1, usize::MAX),
+                combine: Combine::Sum,
+            }
+        }
+
+        pub fn gears() -> Self {
+            Self {
+                symbols: Symbols::OneOf(vec!['*']),
+                neighbours: Arity::Exactly(2),
+                combine: Combine::Product,
+            }
+        }
+
+        pub fn from_str(input: &str) -> Option<Self> {
+            let mut fields = input.rsplitn(3, ':');
+            let combine = match fields.next()? {
+                "product" => Combine::Product,
+                "sum" => Combine::Sum,
+                "max" => Combine::Max,
+                _ => return None,
+            };
+            let count = fields.next()?;
+            let neighbours = match count.split_once('-') {
+                None => Arity::Exactly(count.parse().ok()?),
+                Some((low, "")) => Arity::Between(low.parse().ok()?, usize::MAX),
+                Some((low, high)) => {
+                    let (low, high) = (low.parse().ok()?, high.parse().ok()?);
+                    if low > high {
+                        return None;
+                    }
+                    Arity::Between(low, high)
+                }
+            };
+            let symbols = match fields.next()? {
+                "any" => Symbols::Any,
+                "" => return None,
+                symbols => Symbols::OneOf(symbols.chars().collect()),
+            };
+            Some(Self {
+                symbols,
+                neighbours,
+                combine,
+            })
+        }
+
+        pub fn matches(&self, schematic: &Schematic) -> Vec<(SymbolId, Vec<PartId>)> {
+            let mut results = vec![];
+            for (id, symbol) in schematic.symbols.iter().enumerate() {
+                if !self.symbols.contains(symbol.symbol) {
+                    continue;
+                }
+                let parts = schematic.part_ids_adjacent_to(id);
+                if self.neighbours.accepts(parts.len()) {
+                    results.push((id, parts.to_vec()));
+                }
+            }
+            results
+        }
+
+        pub fn values(&self, schematic: &Schematic) -> Option<Vec<u64>> {
+            self.matches(schematic)
+                .iter()
+                .map(|(id, _)| {
+                    let values: Vec<u64> = schematic
+                        .parts_adjacent_to(*id)
+                        .map(|n| n.value as u64)
+                        .collect();
+                    self.combine.apply(&values)
+                })
+                .collect()
+        }
+
+        pub fn parts(&self, schematic: &Schematic) -> BTreeSet<PartId> {
+            self.matches(schematic)
+                .into_iter()
+                .flat_map(|(_, parts)| parts)
+                .collect()
         }
-        results.append(&mut next.find_adjacent_numbers(&this, &Line::empty()));
-        results
     }
 
     #[test]
-    fn test_find_adjacent_numbers() {
+    fn test_gear_rule_from_str() {
+        assert_eq!(GearRule::from_str("*:2:product"), Some(GearRule::gears()));
         assert_eq!(
-            find_adjacent_numbers(
-                "467..114..
-...*......"
-            ),
-            vec![467]
+            GearRule::from_str("any:1-:sum"),
+            Some(GearRule::part_numbers())
+        );
+        assert_eq!(
+            GearRule::from_str("*#:2-3:max"),
+            Some(GearRule {
+                symbols: Symbols::OneOf(vec!['*', '#']),
+                neighbours: Arity::Between(2, 3),
+                combine: Combine::Max
+            })
+        );
+        assert_eq!(
+            GearRule::from_str("::2:sum"),
+            Some(GearRule {
+                symbols: Symbols::OneOf(vec![':']),
+                neighbours: Arity::Exactly(2),
+                combine: Combine::Sum
+            })
+        );
+        assert_eq!(GearRule::from_str("*:2:mean"), None);
+        assert_eq!(GearRule::from_str("*:two:sum"), None);
+        assert_eq!(GearRule::from_str(":2:sum"), None);
+        assert_eq!(GearRule::from_str("2:sum"), None);
+        assert_eq!(GearRule::from_str("*:3-1:sum"), None);
+        assert_eq!(
+            GearRule::from_str("*:2-2:sum").map(|r| r.neighbours),
+            Some(Arity::Between(2, 2))
+        );
+    }
+
+    #[test]
+    fn test_gear_rules() {
+        let schematic = Schematic::new(
+            "2.3..7
+.*..#.
+4.5...",
+        );
+        assert_eq!(
+            GearRule::from_str("*:4:product")
+                .unwrap()
+                .values(&schematic),
+            Some(vec![120])
+        );
+        assert_eq!(GearRule::gears().values(&schematic), Some(vec![]));
+        assert_eq!(
+            GearRule::from_str("*#:1-:max").unwrap().values(&schematic),
+            Some(vec![5, 7])
+        );
+        assert_eq!(
+            GearRule::from_str("#:1:sum").unwrap().matches(&schematic),
+            vec![(1, vec![2])]
+        );
+        assert_eq!(
+            GearRule::part_numbers().parts(&schematic),
+            BTreeSet::from([0, 1, 2, 3, 4])
+        );
+        assert_eq!(
+            GearRule::from_str("any:3-5:sum")
+                .unwrap()
+                .values(&schematic),
+            Some(vec![14])
+        );
+
+        let large = Schematic::new(
+            "9999999.9999999
+.......*.......
+.......9999999.",
         );
+        let triple = GearRule::from_str("*:3:product").unwrap();
+        assert_eq!(triple.values(&large), None);
         assert_eq!(
-            find_adjacent_numbers(
+            GearRule::from_str("*:3:sum").unwrap().values(&large),
+            Some(vec![29999997])
+        );
+        assert_eq!(crate::task2::handle_input(&large, &triple), None);
+    }
+
+    pub fn handle_input(schematic: &Schematic) -> u64 {
+        GearRule::part_numbers()
+            .parts(schematic)
+            .into_iter()
+            .map(|p| schematic.numbers[p].value as u64)
+            .sum()
+    }
+
+    #[test]
+    fn test_handle_input() {
+        assert_eq!(
+            handle_input(&Schematic::new(
                 "467..114..
 ...*......
-..35..633."
-            ),
-            vec![467, 35]
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598.."
+            )),
+            4361
         );
+    }
+}
+
+mod task2 {
+    use crate::task1::{GearRule, Schematic};
+
+    #[test]
+    fn test_gear_values() {
         assert_eq!(
-            find_adjacent_numbers(
+            GearRule::gears().values(&Schematic::new(
                 "467..114..
 ...*......
 ..35..633.
@@ -222,20 +643,30 @@ mod task1 {
 ......755.
 ...$.*....
 .664.598.."
-            ),
-            vec![467, 35, 633, 617, 592, 664, 755, 598]
+            )),
+            Some(vec![16345, 451490])
+        );
+        assert_eq!(
+            GearRule::gears().values(&Schematic::new(
+                "2*3
+..."
+            )),
+            Some(vec![6])
         );
     }
 
-    pub fn handle_input(input: &str) -> u32 {
-        find_adjacent_numbers(input).iter().sum()
+    pub fn handle_input(schematic: &Schematic, rule: &GearRule) -> Option<u64> {
+        rule.values(schematic)?
+            .iter()
+            .try_fold(0u64, |sum, v| sum.checked_add(*v))
     }
 
     #[test]
     fn test_handle_input() {
         assert_eq!(
             handle_input(
-                "467..114..
+                &Schematic::new(
+                    "467..114..
 ...*......
 ..35..633.
 ......#...
@@ -245,116 +676,248 @@ mod task1 {
 ......755.
 ...$.*....
 .664.598.."
+                ),
+                &GearRule::gears()
             ),
-            4361
+            Some(467835)
         );
     }
 }
 
-mod task2 {
-    use crate::task1::{find_numbers, Line, Symbol};
+mod render {
+    use crate::task1::{GearRule, PartId, Schematic, SymbolId};
+    use std::collections::HashMap;
 
-    fn find_gear_symbols(line: &str) -> Vec<Symbol> {
-        let mut results = vec![];
-        for (i, s) in line.chars().enumerate() {
-            if s == '*' {
-                results.push(Symbol::new(i));
-            }
-        }
-        results
+    const RESET: &str = "\x1b[0m";
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum Cell {
+        Part(PartId),
+        NonPart(PartId),
+        Gear(SymbolId),
+        Symbol(SymbolId),
+        Empty,
     }
 
-    #[test]
-    fn test_find_gear_symbols() {
-        assert_eq!(find_gear_symbols("467..114.."), vec![]);
-        assert_eq!(find_gear_symbols("...*......"), vec![Symbol::new(3)]);
-        assert_eq!(find_gear_symbols("..35..633."), vec![]);
-        assert_eq!(find_gear_symbols("......#..."), vec![]);
-        assert_eq!(find_gear_symbols("617*......"), vec![Symbol::new(3)]);
-        assert_eq!(find_gear_symbols(".....+.58."), vec![]);
-        assert_eq!(find_gear_symbols("..592....."), vec![]);
-        assert_eq!(find_gear_symbols("......755."), vec![]);
-        assert_eq!(find_gear_symbols("...$.*...."), vec![Symbol::new(5)]);
-        assert_eq!(find_gear_symbols(".664.598.."), vec![]);
-    }
-
-    impl Line {
-        fn find_gears(&self, prev: &Line, next: &Line) -> Vec<u32> {
-            let mut results = vec![];
-            for s in &self.symbols {
-                let adjacent_numbers = s.find_adjacent_numbers(prev, self, next);
-                if adjacent_numbers.len() == 2 {
-                    results.push(adjacent_numbers[0] * adjacent_numbers[1]);
-                }
+    impl Cell {
+        fn ansi(&self) -> &'static str {
+            match self {
+                Cell::Part(_) => "\x1b[0;32m",
+                Cell::NonPart(_) => "\x1b[0;31m",
+                Cell::Gear(_) => "\x1b[1;33m",
+                Cell::Symbol(_) => "\x1b[0;36m",
+                Cell::Empty => "\x1b[0;2m",
             }
-            results
         }
 
-        fn new_gears(line: &str) -> Self {
-            Self {
-                symbols: find_gear_symbols(line),
-                numbers: find_numbers(line),
+        fn class(&self) -> &'static str {
+            match self {
+                Cell::Part(_) => "part",
+                Cell::NonPart(_) => "non-part",
+                Cell::Gear(_) => "gear",
+                Cell::Symbol(_) => "symbol",
+                Cell::Empty => "empty",
             }
         }
     }
 
-    fn find_gears(input: &str) -> Vec<u32> {
-        let mut results = vec![];
-
-        let mut this = Line::empty();
-        let mut prev = Line::empty();
-        let mut next = Line::empty();
+    fn cells(schematic: &Schematic, gears: &HashMap<SymbolId, u64>) -> Vec<Vec<(char, Cell)>> {
+        let mut cells: Vec<Vec<(char, Cell)>> = schematic
+            .grid
+            .iter()
+            .map(|row| row.iter().map(|c| (*c, Cell::Empty)).collect())
+            .collect();
+        for (id, number) in schematic.numbers.iter().enumerate() {
+            let cell = if schematic.is_part(id) {
+                Cell::Part(id)
+            } else {
+                Cell::NonPart(id)
+            };
+            for (_, current) in &mut cells[number.row][number.start..number.end] {
+                *current = cell;
+            }
+        }
+        for (id, symbol) in schematic.symbols.iter().enumerate() {
+            cells[symbol.row][symbol.position].1 = if gears.contains_key(&id) {
+                Cell::Gear(id)
+            } else {
+                Cell::Symbol(id)
+            };
+        }
+        cells
+    }
 
-        for l in input.lines() {
-            prev = this;
-            this = next;
-            next = Line::new_gears(l);
+    fn gears(schematic: &Schematic, rule: &GearRule) -> Option<HashMap<SymbolId, u64>> {
+        Some(
+            rule.matches(schematic)
+                .into_iter()
+                .map(|(id, _)| id)
+                .zip(rule.values(schematic)?)
+                .collect(),
+        )
+    }
 
-            results.append(&mut this.find_gears(&prev, &next));
+    pub fn ansi(schematic: &Schematic, rule: &GearRule) -> Option<String> {
+        let mut out = String::new();
+        for row in cells(schematic, &gears(schematic, rule)?) {
+            let mut current = None;
+            for (c, cell) in row {
+                if current != Some(cell.ansi()) {
+                    current = Some(cell.ansi());
+                    out.push_str(cell.ansi());
+                }
+                out.push(c);
+            }
+            out.push_str(RESET);
+            out.push('\n');
         }
-        results.append(&mut next.find_gears(&this, &Line::empty()));
-        results
+        Some(out)
     }
 
     #[test]
-    fn test_find_gears() {
+    fn test_ansi() {
+        let schematic = Schematic::new(
+            "12*3
+..#.
+7.9.",
+        );
         assert_eq!(
-            find_gears(
-                "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598.."
-            ),
-            vec![16345, 451490]
+            ansi(&schematic, &GearRule::gears()).unwrap(),
+            "\x1b[0;32m12\x1b[1;33m*\x1b[0;32m3\x1b[0m
+\x1b[0;2m..\x1b[0;36m#\x1b[0;2m.\x1b[0m
+\x1b[0;31m7\x1b[0;2m.\x1b[0;32m9\x1b[0;2m.\x1b[0m
+"
         );
     }
 
-    pub fn handle_input(input: &str) -> u32 {
-        find_gears(input).iter().sum()
+    fn escape(input: &str) -> String {
+        input
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
     }
 
-    #[test]
-    fn test_handle_input() {
-        assert_eq!(
-            handle_input(
-                "467..114..
-...*......
-..35..633.
-......#...
-617*......
-.....+.58.
-..592.....
-......755.
-...$.*....
-.664.598.."
+    fn location(row: usize, column: usize) -> String {
+        format!("line {}, column {}", row + 1, column + 1)
+    }
+
+    fn tooltip(schematic: &Schematic, gears: &HashMap<SymbolId, u64>, cell: Cell) -> String {
+        let symbol_name = |id: SymbolId| {
+            let symbol = &schematic.symbols[id];
+            format!(
+                "'{}' ({})",
+                symbol.symbol,
+                location(symbol.row, symbol.position)
+            )
+        };
+        let part_values = |id: SymbolId| {
+            schematic
+                .parts_adjacent_to(id)
+                .map(|n| n.value.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match cell {
+            Cell::Part(id) => {
+                let symbols: Vec<String> = schematic
+                    .symbols_adjacent_to(id)
+                    .map(|s| format!("'{}' ({})", s.symbol, location(s.row, s.position)))
+                    .collect();
+                format!(
+                    "part {}, adjacent to {}",
+                    schematic.numbers[id].value,
+                    symbols.join(", ")
+                )
+            }
+            Cell::NonPart(id) => format!(
+                "{}, not adjacent to any symbol",
+                schematic.numbers[id].value
+            ),
+            Cell::Gear(id) => format!(
+                "gear {} = {}, parts {}",
+                symbol_name(id),
+                gears[&id],
+                part_values(id)
             ),
-            467835
+            Cell::Symbol(id) => match part_values(id) {
+                values if values.is_empty() => format!("{}, no adjacent parts", symbol_name(id)),
+                values => format!("{}, parts {values}", symbol_name(id)),
+            },
+            Cell::Empty => String::new(),
+        }
+    }
+
+    pub fn html(schematic: &Schematic, rule: &GearRule) -> Option<String> {
+        let gears = gears(schematic, rule)?;
+        let mut out = String::from(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Schematic</title>
+<style>
+body { background: #111; color: #555; }
+pre { font-size: 14px; line-height: 1.2; }
+.part { color: #4c4; }
+.non-part { color: #c44; }
+.gear { color: #fd3; font-weight: bold; }
+.symbol { color: #4cc; }
+span[title]:hover { background: #333; }
+</style>
+</head>
+<body>
+<pre>
+",
+        );
+        for row in cells(schematic, &gears) {
+            let mut current = Cell::Empty;
+            for (c, cell) in row {
+                if cell != current {
+                    if current != Cell::Empty {
+                        out.push_str("</span>");
+                    }
+                    if cell != Cell::Empty {
+                        out.push_str(&format!(
+                            "<span class=\"{}\" title=\"{}\">",
+                            cell.class(),
+                            escape(&tooltip(schematic, &gears, cell))
+                        ));
+                    }
+                    current = cell;
+                }
+                out.push_str(&escape(&c.to_string()));
+            }
+            if current != Cell::Empty {
+                out.push_str("</span>");
+            }
+            out.push('\n');
+        }
+        out.push_str("</pre>\n</body>\n</html>\n");
+        Some(out)
+    }
+
+    #[test]
+    fn test_html() {
+        let schematic = Schematic::new(
+            "12*3
+..&.
+7.9.",
         );
+        let html = html(&schematic, &GearRule::gears()).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert!(html.contains(
+            "<span class=\"part\" title=\"part 12, adjacent to '*' (line 1, column 3), '&amp;' (line 2, column 3)\">12</span>"
+        ));
+        assert!(html.contains(
+            "<span class=\"gear\" title=\"gear '*' (line 1, column 3) = 36, parts 12, 3\">*</span>"
+        ));
+        assert!(html.contains(
+            "<span class=\"symbol\" title=\"'&amp;' (line 2, column 3), parts 12, 3, 9\">&amp;</span>"
+        ));
+        assert!(html
+            .contains("<span class=\"non-part\" title=\"7, not adjacent to any symbol\">7</span>"));
+        assert!(html.contains("\n..<span"));
     }
 }
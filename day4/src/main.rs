@@ -2,86 +2,418 @@ use std::fs::File;
 use std::io::prelude::*;
 
 fn main() -> std::io::Result<()> {
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        let flag = |name: &str, default: usize| -> std::io::Result<usize> {
+            match std::env::args().find_map(|a| a.strip_prefix(name).map(String::from)) {
+                Some(v) => v.parse::<usize>().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("invalid {name}{v:?}: {e}"),
+                    )
+                }),
+                None => Ok(default),
+            }
+        };
+        print!(
+            "{}",
+            bench::run(flag("--cards=", 1000)?, flag("--numbers=", 300)?)
+        );
+        return Ok(());
+    }
+
     let mut file = File::open("input.txt")?;
     let mut input = String::new();
     file.read_to_string(&mut input)?;
 
-    let t1_sum = task1::handle_input(&input);
+    let cards = task1::parse_cards(&input)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+    let overflow = || std::io::Error::new(std::io::ErrorKind::InvalidData, "score overflows u64");
+
+    if let Some(path) =
+        std::env::args().find_map(|a| a.strip_prefix("--trace-dot=").map(String::from))
+    {
+        File::create(&path)?
+            .write_all(trace::to_dot(&trace::trace(&cards).ok_or_else(overflow)?).as_bytes())?;
+        println!("Wrote {path}");
+    }
+    if let Some(path) =
+        std::env::args().find_map(|a| a.strip_prefix("--trace-csv=").map(String::from))
+    {
+        File::create(&path)?
+            .write_all(trace::to_csv(&trace::trace(&cards).ok_or_else(overflow)?).as_bytes())?;
+        println!("Wrote {path}");
+    }
+
+    let t1_sum = task1::handle_input(&cards).ok_or_else(overflow)?;
     println!("Task1 answer: {t1_sum}");
 
-    let t2_sum = task2::handle_input(&input);
+    let t2_sum = task2::handle_input(&cards).ok_or_else(overflow)?;
     println!("Task2 answer: {t2_sum}");
 
+    if let Some(r) = std::env::args().find_map(|a| a.strip_prefix("--rule=").map(String::from)) {
+        let rule = task2::rule_from_str(&r).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "unknown scoring rule {r:?}, expected doubling, cascade, linear[:N], \
+                     capped-cascade:N or multiplied-cascade:N"
+                ),
+            )
+        })?;
+        let score = rule.score(&cards).ok_or_else(overflow)?;
+        println!("{} answer: {score}", rule.name());
+    }
+
     Ok(())
 }
 
 mod task1 {
-    pub fn handle_input(input: &str) -> u32 {
-        input.lines().map(|l| line_value(l)).sum()
+    use std::fmt;
+
+    #[derive(Debug, PartialEq)]
+    pub enum CardError {
+        InvalidHeader(String),
+        MissingSeparator(String),
+        InvalidNumber(String),
+        DuplicateNumber { id: u32, number: u32 },
+        DuplicateId(u32),
+        MissingId(u32),
+    }
+
+    impl fmt::Display for CardError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                CardError::InvalidHeader(s) => write!(f, "invalid card header {s:?}"),
+                CardError::MissingSeparator(s) => write!(f, "missing '|' in {s:?}"),
+                CardError::InvalidNumber(s) => write!(f, "invalid number {s:?}"),
+                CardError::DuplicateNumber { id, number } => {
+                    write!(f, "card {id} lists {number} twice on one side")
+                }
+                CardError::DuplicateId(id) => write!(f, "card {id} appears more than once"),
+                CardError::MissingId(id) => write!(f, "card {id} is missing"),
+            }
+        }
+    }
+
+    impl std::error::Error for CardError {}
+
+    #[derive(Debug, Default, PartialEq)]
+    pub struct NumberSet {
+        words: [u64; 2],
+        large: Vec<u32>,
+    }
+
+    impl NumberSet {
+        pub fn from_numbers(numbers: &[u32]) -> Result<Self, u32> {
+            let mut set = Self::default();
+            for &number in numbers {
+                if !set.insert(number) {
+                    return Err(number);
+                }
+            }
+            Ok(set)
+        }
+
+        pub fn insert(&mut self, number: u32) -> bool {
+            if number < 128 {
+                let (word, bit) = ((number / 64) as usize, 1 << (number % 64));
+                let added = self.words[word] & bit == 0;
+                self.words[word] |= bit;
+                return added;
+            }
+            match self.large.binary_search(&number) {
+                Ok(_) => false,
+                Err(i) => {
+                    self.large.insert(i, number);
+                    true
+                }
+            }
+        }
+
+        #[cfg(test)]
+        pub fn len(&self) -> usize {
+            self.words
+                .iter()
+                .map(|w| w.count_ones() as usize)
+                .sum::<usize>()
+                + self.large.len()
+        }
+
+        pub fn intersection_len(&self, other: &NumberSet) -> usize {
+            let small: usize = self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(a, b)| (a & b).count_ones() as usize)
+                .sum();
+            let (mut i, mut j, mut large) = (0, 0, 0);
+            while i < self.large.len() && j < other.large.len() {
+                match self.large[i].cmp(&other.large[j]) {
+                    std::cmp::Ordering::Less => i += 1,
+                    std::cmp::Ordering::Greater => j += 1,
+                    std::cmp::Ordering::Equal => {
+                        large += 1;
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            }
+            small + large
+        }
+    }
+
+    #[test]
+    fn test_number_set() {
+        let a = NumberSet::from_numbers(&[0, 63, 64, 127, 128, 1000, 5]).unwrap();
+        let b = NumberSet::from_numbers(&[1000, 63, 6, 127, 129, 0]).unwrap();
+        assert_eq!(a.len(), 7);
+        assert_eq!(b.len(), 6);
+        assert_eq!(a.intersection_len(&b), 4);
+        assert_eq!(b.intersection_len(&a), 4);
+        assert_eq!(a.intersection_len(&NumberSet::default()), 0);
+        assert_eq!(NumberSet::from_numbers(&[3, 200, 3]), Err(3));
+        assert_eq!(NumberSet::from_numbers(&[200, 3, 200]), Err(200));
+        assert_eq!(NumberSet::from_numbers(&[u32::MAX]).unwrap().len(), 1);
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Card {
+        pub id: u32,
+        pub winning: NumberSet,
+        pub have: NumberSet,
+    }
+
+    impl Card {
+        pub fn from_str(line: &str) -> Result<Self, CardError> {
+            let (header, numbers) = line
+                .trim()
+                .split_once(":")
+                .ok_or_else(|| CardError::InvalidHeader(line.to_string()))?;
+            let id = header
+                .strip_prefix("Card")
+                .and_then(|id| id.trim().parse::<u32>().ok())
+                .ok_or_else(|| CardError::InvalidHeader(header.to_string()))?;
+            let (winning, have) = numbers
+                .split_once("|")
+                .ok_or_else(|| CardError::MissingSeparator(line.to_string()))?;
+            let side = |numbers| {
+                NumberSet::from_numbers(&str_to_list(numbers)?)
+                    .map_err(|number| CardError::DuplicateNumber { id, number })
+            };
+            Ok(Self {
+                id,
+                winning: side(winning)?,
+                have: side(have)?,
+            })
+        }
+
+        pub fn matches(&self) -> usize {
+            self.winning.intersection_len(&self.have)
+        }
+    }
+
+    #[test]
+    fn test_parse_card() {
+        assert_eq!(
+            Card::from_str("Card 1: 41 48 83 | 83 86  6"),
+            Ok(Card {
+                id: 1,
+                winning: NumberSet::from_numbers(&[41, 48, 83]).unwrap(),
+                have: NumberSet::from_numbers(&[83, 86, 6]).unwrap()
+            })
+        );
+        assert_eq!(
+            Card::from_str("Card   12:  1 | 2"),
+            Ok(Card {
+                id: 12,
+                winning: NumberSet::from_numbers(&[1]).unwrap(),
+                have: NumberSet::from_numbers(&[2]).unwrap()
+            })
+        );
+        assert_eq!(
+            Card::from_str("Crad 1: 1 | 2"),
+            Err(CardError::InvalidHeader("Crad 1".to_string()))
+        );
+        assert_eq!(
+            Card::from_str("1 | 2"),
+            Err(CardError::InvalidHeader("1 | 2".to_string()))
+        );
+        assert_eq!(
+            Card::from_str("Card 1: 1 2"),
+            Err(CardError::MissingSeparator("Card 1: 1 2".to_string()))
+        );
+        assert_eq!(
+            Card::from_str("Card 1: 1 x | 2"),
+            Err(CardError::InvalidNumber("x".to_string()))
+        );
+        assert_eq!(
+            Card::from_str("Card 3: 1 2 | 5 7 5"),
+            Err(CardError::DuplicateNumber { id: 3, number: 5 })
+        );
+    }
+
+    pub fn parse_cards(input: &str) -> Result<Vec<Card>, CardError> {
+        let mut cards = input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(Card::from_str)
+            .collect::<Result<Vec<Card>, CardError>>()?;
+        cards.sort_by_key(|c| c.id);
+        for pair in cards.windows(2) {
+            if pair[0].id == pair[1].id {
+                return Err(CardError::DuplicateId(pair[0].id));
+            }
+            if pair[0].id + 1 != pair[1].id {
+                return Err(CardError::MissingId(pair[0].id + 1));
+            }
+        }
+        Ok(cards)
+    }
+
+    #[test]
+    fn test_parse_cards() {
+        assert_eq!(
+            parse_cards("Card 2: 1 | 2\nCard 1: 3 | 4")
+                .unwrap()
+                .iter()
+                .map(|c| c.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            parse_cards("Card 1: 1 | 2\nCard 1: 3 | 4"),
+            Err(CardError::DuplicateId(1))
+        );
+        assert_eq!(
+            parse_cards("Card 1: 1 | 2\nCard 4: 3 | 4"),
+            Err(CardError::MissingId(2))
+        );
+    }
+
+    pub trait ScoringRule {
+        fn name(&self) -> String;
+        fn score(&self, cards: &[Card]) -> Option<u64>;
+    }
+
+    pub struct Doubling;
+
+    impl ScoringRule for Doubling {
+        fn name(&self) -> String {
+            "doubling".to_string()
+        }
+
+        fn score(&self, cards: &[Card]) -> Option<u64> {
+            cards
+                .iter()
+                .try_fold(0u64, |sum, card| sum.checked_add(card_value(card)?))
+        }
+    }
+
+    pub struct Linear {
+        pub points: u64,
+    }
+
+    impl ScoringRule for Linear {
+        fn name(&self) -> String {
+            format!("linear:{}", self.points)
+        }
+
+        fn score(&self, cards: &[Card]) -> Option<u64> {
+            cards.iter().try_fold(0u64, |sum, card| {
+                sum.checked_add((card.matches() as u64).checked_mul(self.points)?)
+            })
+        }
+    }
+
+    #[test]
+    fn test_scoring_rules() {
+        let cards = parse_cards(
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        )
+        .unwrap();
+        assert_eq!(Doubling.score(&cards), Some(13));
+        assert_eq!(Linear { points: 1 }.score(&cards), Some(9));
+        assert_eq!(Linear { points: 5 }.score(&cards), Some(45));
+        assert_eq!(Linear { points: u64::MAX }.score(&cards), None);
+
+        let numbers: Vec<String> = (1..=70).map(|n| n.to_string()).collect();
+        let numbers = numbers.join(" ");
+        let big = parse_cards(&format!("Card 1: {numbers} | {numbers}")).unwrap();
+        assert_eq!(Doubling.score(&big), None);
+        assert_eq!(Linear { points: 2 }.score(&big), Some(140));
+    }
+
+    pub fn handle_input(cards: &[Card]) -> Option<u64> {
+        Doubling.score(cards)
     }
 
     #[test]
     fn test_handle_input() {
         assert_eq!(
             handle_input(
-                "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+                &parse_cards(
+                    "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
 Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
 Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
+                )
+                .unwrap()
             ),
-            13
+            Some(13)
         );
     }
 
-    fn line_value(line: &str) -> u32 {
-        let matches = matches(line);
+    fn card_value(card: &Card) -> Option<u64> {
+        let matches = card.matches() as u32;
         if matches != 0 {
-            return u32::pow(2, matches - 1);
+            return 2u64.checked_pow(matches - 1);
         }
-        0
+        Some(0)
     }
 
     #[test]
-    fn test_line_values() {
+    fn test_card_values() {
+        let value = |line| card_value(&Card::from_str(line).unwrap());
         assert_eq!(
-            line_value("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
-            8
+            value("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
+            Some(8)
         );
         assert_eq!(
-            line_value(
+            value(
                 "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
             "
             ),
-            2
+            Some(2)
         );
         assert_eq!(
-            line_value("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
-            2
+            value("Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"),
+            Some(2)
         );
         assert_eq!(
-            line_value("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
-            1
+            value("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83"),
+            Some(1)
         );
         assert_eq!(
-            line_value("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
-            0
+            value("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36"),
+            Some(0)
         );
         assert_eq!(
-            line_value("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
-            0
+            value("Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"),
+            Some(0)
         );
     }
 
-    pub fn matches(line: &str) -> u32 {
-        let (winning_numbers, numbers_you_have) =
-            line.split_once(":").unwrap().1.split_once("|").unwrap();
-        return intersect(str_to_list(winning_numbers), str_to_list(numbers_you_have)).len() as u32;
-    }
-
     #[test]
     fn test_matches() {
+        let matches = |line| Card::from_str(line).unwrap().matches();
         assert_eq!(
             matches("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"),
             4
@@ -111,88 +443,451 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
         );
     }
 
-    fn intersect(a: Vec<u32>, b: Vec<u32>) -> Vec<u32> {
-        a.into_iter().filter(|i| b.contains(i)).collect()
+    pub fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+        a.iter().filter(|i| b.contains(i)).copied().collect()
     }
 
     #[test]
     fn test_intersect() {
         assert_eq!(
-            intersect(vec![41, 48, 83, 86, 17], vec![83, 86, 6, 31, 17, 9, 48, 53]),
+            intersect(&[41, 48, 83, 86, 17], &[83, 86, 6, 31, 17, 9, 48, 53]),
             vec![48, 83, 86, 17]
         );
     }
 
-    fn str_to_list(input: &str) -> Vec<u32> {
+    pub fn str_to_list(input: &str) -> Result<Vec<u32>, CardError> {
         input
-            .trim()
-            .split(" ")
-            .filter_map(|i| i.trim().parse::<u32>().ok())
-            .collect::<Vec<u32>>()
+            .split_whitespace()
+            .map(|i| {
+                i.parse::<u32>()
+                    .map_err(|_| CardError::InvalidNumber(i.to_string()))
+            })
+            .collect()
     }
 
     #[test]
     fn test_str_to_list() {
-        assert_eq!(str_to_list(" 41 48 83 86 17 "), vec![41, 48, 83, 86, 17]);
+        assert_eq!(
+            str_to_list(" 41 48 83 86 17 "),
+            Ok(vec![41, 48, 83, 86, 17])
+        );
         assert_eq!(
             str_to_list(" 83 86  6 31 17  9 48 53"),
-            vec![83, 86, 6, 31, 17, 9, 48, 53]
+            Ok(vec![83, 86, 6, 31, 17, 9, 48, 53])
+        );
+        assert_eq!(str_to_list(" "), Ok(vec![]));
+        assert_eq!(
+            str_to_list("1 -2"),
+            Err(CardError::InvalidNumber("-2".to_string()))
         );
     }
 }
 
 mod task2 {
-    use crate::task1::matches;
+    use crate::task1::{Card, Doubling, Linear, ScoringRule};
+    use crate::trace::trace_with;
 
-    struct Scratchcard {
-        matches: usize,
-        count: u32,
+    fn cascade(cards: &[Card], cap: usize, multiplier: u64) -> Option<u64> {
+        trace_with(cards, cap, multiplier)?
+            .iter()
+            .try_fold(0u64, |sum, t| sum.checked_add(t.instances))
     }
 
-    impl Scratchcard {
-        fn new(matches: usize) -> Self {
-            Self { matches, count: 1 }
+    pub struct Cascade;
+
+    impl ScoringRule for Cascade {
+        fn name(&self) -> String {
+            "cascade".to_string()
+        }
+
+        fn score(&self, cards: &[Card]) -> Option<u64> {
+            cascade(cards, usize::MAX, 1)
         }
     }
 
-    pub fn handle_input(input: &str) -> u32 {
-        let mut scratchcards: Vec<Scratchcard> = input
-            .lines()
-            .map(|l| Scratchcard::new(matches(l) as usize))
-            .collect();
+    pub struct CappedCascade {
+        pub cap: usize,
+    }
 
-        for current in 0..scratchcards.len() {
-            let s = scratchcards.get(current).unwrap();
-            let matches = s.matches;
-            let current_count = s.count;
-
-            for i in 0..matches {
-                let next = current + i + 1;
-                let copy = scratchcards.get_mut(next);
-                match copy {
-                    Some(copy) => {
-                        copy.count += current_count;
-                    }
-                    None => {}
-                }
-            }
+    impl ScoringRule for CappedCascade {
+        fn name(&self) -> String {
+            format!("capped-cascade:{}", self.cap)
         }
 
-        scratchcards.into_iter().map(|s| s.count).sum()
+        fn score(&self, cards: &[Card]) -> Option<u64> {
+            cascade(cards, self.cap, 1)
+        }
+    }
+
+    pub struct MultipliedCascade {
+        pub multiplier: u64,
+    }
+
+    impl ScoringRule for MultipliedCascade {
+        fn name(&self) -> String {
+            format!("multiplied-cascade:{}", self.multiplier)
+        }
+
+        fn score(&self, cards: &[Card]) -> Option<u64> {
+            cascade(cards, usize::MAX, self.multiplier)
+        }
+    }
+
+    pub fn rule_from_str(input: &str) -> Option<Box<dyn ScoringRule>> {
+        let (name, argument) = match input.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (input, None),
+        };
+        match (name, argument) {
+            ("doubling", None) => Some(Box::new(Doubling)),
+            ("cascade", None) => Some(Box::new(Cascade)),
+            ("linear", Some(a)) => Some(Box::new(Linear {
+                points: a.parse().ok()?,
+            })),
+            ("linear", None) => Some(Box::new(Linear { points: 1 })),
+            ("capped-cascade", Some(a)) => Some(Box::new(CappedCascade {
+                cap: a.parse().ok()?,
+            })),
+            ("multiplied-cascade", Some(a)) => Some(Box::new(MultipliedCascade {
+                multiplier: a.parse().ok()?,
+            })),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_cascade_rules() {
+        use crate::task1::parse_cards;
+        let cards = parse_cards(
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+        )
+        .unwrap();
+        assert_eq!(Cascade.score(&cards), Some(30));
+        assert_eq!(CappedCascade { cap: 1 }.score(&cards), Some(16));
+        assert_eq!(CappedCascade { cap: 0 }.score(&cards), Some(6));
+        assert_eq!(MultipliedCascade { multiplier: 1 }.score(&cards), Some(30));
+        assert_eq!(MultipliedCascade { multiplier: 2 }.score(&cards), Some(116));
+        assert_eq!(
+            MultipliedCascade {
+                multiplier: u64::MAX
+            }
+            .score(&cards),
+            None
+        );
+
+        let last = parse_cards("Card 4294967294: 1 2 | 1 2\nCard 4294967295: 1 | 1").unwrap();
+        assert_eq!(Cascade.score(&last), Some(3));
+        assert_eq!(CappedCascade { cap: 0 }.score(&last), Some(2));
+    }
+
+    #[test]
+    fn test_rule_from_str() {
+        let name = |input| rule_from_str(input).map(|r| r.name());
+        assert_eq!(name("doubling"), Some("doubling".to_string()));
+        assert_eq!(name("cascade"), Some("cascade".to_string()));
+        assert_eq!(name("linear"), Some("linear:1".to_string()));
+        assert_eq!(name("linear:3"), Some("linear:3".to_string()));
+        assert_eq!(
+            name("capped-cascade:2"),
+            Some("capped-cascade:2".to_string())
+        );
+        assert_eq!(
+            name("multiplied-cascade:3"),
+            Some("multiplied-cascade:3".to_string())
+        );
+        assert_eq!(name("capped-cascade"), None);
+        assert_eq!(name("linear:x"), None);
+        assert_eq!(name("cascade:2"), None);
+        assert_eq!(name("random"), None);
+    }
+
+    pub fn handle_input(cards: &[Card]) -> Option<u64> {
+        Cascade.score(cards)
     }
 
     #[test]
     fn test_handle_input() {
+        use crate::task1::parse_cards;
         assert_eq!(
             handle_input(
-                "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+                &parse_cards(
+                    "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
 Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
 Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"
+                )
+                .unwrap()
+            ),
+            Some(30)
+        );
+        assert_eq!(
+            handle_input(
+                &parse_cards(
+                    "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1"
+                )
+                .unwrap()
             ),
-            30
+            Some(30)
         );
     }
 }
+
+mod trace {
+    use crate::task1::Card;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq)]
+    pub struct CardTrace {
+        pub id: u32,
+        pub matches: usize,
+        pub instances: u64,
+        pub sources: Vec<(u32, u64)>,
+        pub copies_contributed: u64,
+    }
+
+    impl CardTrace {
+        pub fn copies_received(&self) -> u64 {
+            self.sources.iter().map(|(_, copies)| copies).sum()
+        }
+    }
+
+    pub fn trace(cards: &[Card]) -> Option<Vec<CardTrace>> {
+        trace_with(cards, usize::MAX, 1)
+    }
+
+    pub fn trace_with(cards: &[Card], cap: usize, multiplier: u64) -> Option<Vec<CardTrace>> {
+        let index: HashMap<u32, usize> = cards.iter().enumerate().map(|(i, c)| (c.id, i)).collect();
+        let mut traces: Vec<CardTrace> = cards
+            .iter()
+            .map(|c| CardTrace {
+                id: c.id,
+                matches: c.matches(),
+                instances: 1,
+                sources: vec![],
+                copies_contributed: 0,
+            })
+            .collect();
+
+        for current in 0..traces.len() {
+            let (id, matches) = (traces[current].id, traces[current].matches.min(cap));
+            let won = traces[current].instances.checked_mul(multiplier)?;
+            let last = id.saturating_add(u32::try_from(matches).unwrap_or(u32::MAX));
+            for next in (id..=last).skip(1) {
+                if let Some(&target) = index.get(&next) {
+                    traces[target].instances = traces[target].instances.checked_add(won)?;
+                    traces[target].sources.push((id, won));
+                    traces[current].copies_contributed =
+                        traces[current].copies_contributed.checked_add(won)?;
+                }
+            }
+        }
+        Some(traces)
+    }
+
+    pub fn to_csv(traces: &[CardTrace]) -> String {
+        let mut out = String::from("card,matches,copies_received,copies_contributed\n");
+        for t in traces {
+            out.push_str(&format!(
+                "{},{},{},{}\n",
+                t.id,
+                t.matches,
+                t.copies_received(),
+                t.copies_contributed
+            ));
+        }
+        out
+    }
+
+    pub fn to_dot(traces: &[CardTrace]) -> String {
+        let mut out = String::from("digraph cascade {\n    rankdir=LR;\n");
+        for t in traces {
+            out.push_str(&format!(
+                "    card{} [label=\"Card {}\\nmatches: {}\\ninstances: {}\"];\n",
+                t.id, t.id, t.matches, t.instances
+            ));
+        }
+        for t in traces {
+            for (source, copies) in &t.sources {
+                out.push_str(&format!(
+                    "    card{source} -> card{} [label=\"{copies}\"];\n",
+                    t.id
+                ));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    #[test]
+    fn test_trace() {
+        use crate::task1::parse_cards;
+        let traces = trace(
+            &parse_cards(
+                "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            traces.iter().map(|t| t.instances).collect::<Vec<_>>(),
+            vec![1, 2, 4, 8, 14, 1]
+        );
+        assert_eq!(traces[4].sources, vec![(1, 1), (3, 4), (4, 8)]);
+        assert_eq!(
+            to_csv(&traces),
+            "card,matches,copies_received,copies_contributed
+1,4,0,4
+2,2,1,4
+3,2,3,8
+4,1,7,8
+5,0,13,0
+6,0,0,0
+"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        use crate::task1::parse_cards;
+        let traces =
+            trace(&parse_cards("Card 1: 1 2 | 1 2\nCard 2: 3 | 3\nCard 3: 4 | 5").unwrap())
+                .unwrap();
+        assert_eq!(
+            to_dot(&traces),
+            "digraph cascade {
+    rankdir=LR;
+    card1 [label=\"Card 1\\nmatches: 2\\ninstances: 1\"];
+    card2 [label=\"Card 2\\nmatches: 1\\ninstances: 2\"];
+    card3 [label=\"Card 3\\nmatches: 0\\ninstances: 4\"];
+    card1 -> card2 [label=\"1\"];
+    card1 -> card3 [label=\"1\"];
+    card2 -> card3 [label=\"2\"];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_trace_overflow() {
+        use crate::task1::parse_cards;
+        let lines: Vec<String> = (1..=70)
+            .map(|id| {
+                let numbers: Vec<String> = (id..70).map(|n| n.to_string()).collect();
+                let numbers = numbers.join(" ");
+                format!("Card {id}: {numbers} | {numbers}")
+            })
+            .collect();
+        let cards = parse_cards(&lines.join("\n")).unwrap();
+        assert_eq!(trace(&cards[..64]).unwrap()[63].instances, 1 << 63);
+        assert_eq!(trace(&cards), None);
+    }
+}
+
+mod bench {
+    use crate::task1::{intersect, str_to_list, Card};
+    use std::time::{Duration, Instant};
+
+    fn shuffle<T>(state: &mut u64, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            items.swap(i, (*state % (i as u64 + 1)) as usize);
+        }
+    }
+
+    pub fn synthetic_cards(cards: usize, numbers: usize, seed: u64) -> Vec<String> {
+        let mut state = seed.max(1);
+        let mut pool: Vec<u32> = (0..(numbers * 4).max(128) as u32).collect();
+        (1..=cards)
+            .map(|id| {
+                shuffle(&mut state, &mut pool);
+                let shared = id % (numbers + 1);
+                let mut have: Vec<u32> = pool[..shared]
+                    .iter()
+                    .chain(&pool[numbers..2 * numbers - shared])
+                    .copied()
+                    .collect();
+                shuffle(&mut state, &mut have);
+                let join = |side: &[u32]| {
+                    side.iter()
+                        .map(|n| n.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                format!("Card {id}: {} | {}", join(&pool[..numbers]), join(&have))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_synthetic_cards() {
+        for numbers in [5, 64, 300] {
+            for line in synthetic_cards(20, numbers, 7) {
+                let card = Card::from_str(&line).unwrap();
+                let (winning, have) = line.split_once(':').unwrap().1.split_once('|').unwrap();
+                let expected =
+                    intersect(&str_to_list(winning).unwrap(), &str_to_list(have).unwrap());
+                assert_eq!(card.winning.len(), numbers);
+                assert_eq!(card.have.len(), numbers);
+                assert_eq!(card.matches(), expected.len());
+            }
+        }
+    }
+
+    fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+        let start = Instant::now();
+        let result = f();
+        (result, start.elapsed())
+    }
+
+    pub fn run(cards: usize, numbers: usize) -> String {
+        let lines = synthetic_cards(cards, numbers, 0x5eed);
+        let (lists, _) = time(|| {
+            lines
+                .iter()
+                .map(|line| {
+                    let (winning, have) = line.split_once(':').unwrap().1.split_once('|').unwrap();
+                    (str_to_list(winning).unwrap(), str_to_list(have).unwrap())
+                })
+                .collect::<Vec<_>>()
+        });
+        let (parsed, parse_time) = time(|| {
+            lines
+                .iter()
+                .map(|line| Card::from_str(line).unwrap())
+                .collect::<Vec<_>>()
+        });
+        let (naive, naive_time) = time(|| {
+            lists
+                .iter()
+                .map(|(w, h)| intersect(w, h).len())
+                .sum::<usize>()
+        });
+        let (bitset, bitset_time) = time(|| parsed.iter().map(Card::matches).sum::<usize>());
+
+        format!(
+            "{cards} cards, {numbers} numbers per side\n\
+             parse:     {parse_time:?}\n\
+             intersect: {naive_time:?} ({naive} matches)\n\
+             bitset:    {bitset_time:?} ({bitset} matches)\n"
+        )
+    }
+}